@@ -1,91 +1,106 @@
+pub use crate::day10::cpu::Breakpoint;
+pub use crate::day10::registers::Registers;
+
+mod assembler;
+mod cpu;
 mod instruction;
+mod registers;
 
 pub struct Day10;
 
-use crate::day10::instruction::Instruction;
-use std::collections::LinkedList;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::str::FromStr;
+use crate::day10::assembler::Program;
+use crate::day10::cpu::Cpu;
+use crate::day10::instruction::InstructionTable;
+use crate::ocr;
+use std::fs::read_to_string;
 
 const LINE_LEN: usize = 40;
 
-struct Sprite {
-    register: i32,
-}
-
-impl Default for Sprite {
-    fn default() -> Self {
-        Self { register: 1 }
+impl Day10 {
+    fn assemble(path: &str) -> Result<Program, String> {
+        Program::assemble(&read_to_string(path).unwrap(), &InstructionTable::default()).map_err(
+            |diagnostics| {
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        )
     }
-}
 
-impl Sprite {
-    fn increment_register(&mut self, instruction: Instruction) {
-        self.register = instruction.register_increment(self.register);
+    fn cpu(path: &str) -> Cpu {
+        let program = Self::assemble(path).unwrap_or_else(|error| panic!("{}", error));
+
+        Cpu::new(program.instructions, &InstructionTable::default()).unwrap()
     }
 
-    fn char(&self, position: usize) -> char {
-        let position = (position % LINE_LEN) as i32;
+    /// Validates the program at `path` without running it and summarizes what it does
+    pub fn check(path: &str) -> Result<String, String> {
+        let table = InstructionTable::default();
+        let program = Self::assemble(path)?;
+
+        Ok(format!(
+            "{} instructions, {} cycles, final registers {}",
+            program.instructions.len(),
+            program.cycles(&table),
+            program.final_registers(&table)
+        ))
+    }
 
-        if position < self.register - 1 || position > self.register + 1 {
-            return '.';
-        }
+    /// Every cycle of the program at `path` stopping at one of `breakpoints`, with the
+    /// registers during that cycle and the first breakpoint it hit
+    pub fn debug(
+        path: &str,
+        breakpoints: &[Breakpoint],
+    ) -> Result<Vec<(usize, Registers, Breakpoint)>, String> {
+        let mut cpu = Cpu::new(
+            Self::assemble(path)?.instructions,
+            &InstructionTable::default(),
+        )?;
+
+        breakpoints
+            .iter()
+            .for_each(|breakpoint| cpu.add_breakpoint(breakpoint.clone()));
+
+        Ok(std::iter::from_fn(|| cpu.resume()).collect())
+    }
 
-        '#'
+    /// Canonical listing of the program at `path`
+    pub fn disassemble(path: &str) -> Result<String, String> {
+        Ok(Self::assemble(path)?.disassemble())
     }
-}
 
-impl Day10 {
     pub fn part_one() -> i32 {
-        let mut cycles_of_interest = LinkedList::from([20, 60, 100, 140, 180, 220]);
-        let mut cycle_now = 0;
-        let mut register_now = 1;
+        let mut cpu = Self::cpu("src/day10/input");
         let mut result = 0;
-        let buf_reader = BufReader::new(File::open("src/day10/input").unwrap());
-
-        for line in buf_reader.lines() {
-            if cycles_of_interest.is_empty() {
-                break;
-            }
-
-            let cycle_of_interest = *cycles_of_interest.front().unwrap();
-            let register_before = register_now;
 
-            let instruction = Instruction::from_str(line.as_ref().unwrap()).unwrap();
+        [20, 60, 100, 140, 180, 220]
+            .into_iter()
+            .for_each(|cycle| cpu.add_breakpoint(Breakpoint::Cycle(cycle)));
 
-            cycle_now = instruction.cycle_increment(cycle_now);
-            register_now = instruction.register_increment(register_now);
-
-            // After applying the instruction I end after the cycle
-            if cycle_now >= cycle_of_interest {
-                result += cycle_of_interest as i32 * register_before;
-                cycles_of_interest.pop_front();
-            }
+        while let Some((cycle, registers, _)) = cpu.resume() {
+            result += cycle as i32 * registers['x'];
         }
 
         result
     }
 
-    pub fn part_two() -> String {
-        let buf_reader = BufReader::new(File::open("src/day10/input").unwrap());
-        let mut chars = vec![];
+    /// Pixels drawn by the CRT, one line per row
+    pub fn crt() -> String {
         let mut result = String::default();
-        let mut sprite = Sprite::default();
-        let mut cycle_now = 0;
-
-        buf_reader.lines().for_each(|line| {
-            let instruction = Instruction::from_str(line.as_ref().unwrap()).unwrap();
-
-            let cycle_future = instruction.cycle_increment(cycle_now);
-
-            (cycle_now..cycle_future).for_each(|cycle| {
-                chars.push(sprite.char(cycle));
-            });
-
-            sprite.increment_register(instruction);
-            cycle_now = cycle_future;
-        });
+        let chars = Self::cpu("src/day10/input")
+            .map(|(cycle, registers)| {
+                // The sprite is 3 pixels wide and centered on `x`
+                let position = ((cycle - 1) % LINE_LEN) as i32;
+
+                if (position - registers['x']).abs() <= 1 {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect::<Vec<_>>();
 
         chars.chunks(LINE_LEN).for_each(|chunk| {
             result.extend(chunk);
@@ -94,4 +109,13 @@ impl Day10 {
 
         result
     }
+
+    /// Cycle by cycle listing of the instructions being executed and the registers
+    pub fn trace() -> String {
+        Self::cpu("src/day10/input").trace()
+    }
+
+    pub fn part_two() -> String {
+        ocr::recognize(&Self::crt()).unwrap_or_else(|error| panic!("{}", error))
+    }
 }
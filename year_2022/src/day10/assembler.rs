@@ -0,0 +1,118 @@
+use crate::day10::instruction::{Instruction, InstructionTable};
+use crate::day10::registers::Registers;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Problem found on a line of the source, lines are numbered from 1
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Diagnostic {
+    pub(crate) line: usize,
+    pub(crate) message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Program {
+    pub(crate) instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Parses and validates a whole program against `table`, blank lines are skipped.
+    ///
+    /// Every faulty line is reported, not only the first one.
+    pub(crate) fn assemble(
+        source: &str,
+        table: &InstructionTable,
+    ) -> Result<Self, Vec<Diagnostic>> {
+        let mut instructions = vec![];
+        let mut diagnostics = vec![];
+
+        source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .for_each(|(i, line)| {
+                match Instruction::from_str(line)
+                    .and_then(|instruction| table.opcode(&instruction).map(|_| instruction))
+                {
+                    Ok(instruction) => instructions.push(instruction),
+                    Err(message) => diagnostics.push(Diagnostic {
+                        line: i + 1,
+                        message,
+                    }),
+                }
+            });
+
+        if diagnostics.is_empty() {
+            Ok(Self { instructions })
+        } else {
+            Err(diagnostics)
+        }
+    }
+
+    /// Canonical source of the program, one instruction per line
+    pub(crate) fn disassemble(&self) -> String {
+        self.instructions
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Number of cycles the program takes, without running it cycle by cycle
+    pub(crate) fn cycles(&self, table: &InstructionTable) -> usize {
+        self.instructions
+            .iter()
+            .map(|instruction| table.opcode(instruction).unwrap().cycles)
+            .sum()
+    }
+
+    /// Registers once the last instruction is over, applying each instruction once
+    pub(crate) fn final_registers(&self, table: &InstructionTable) -> Registers {
+        let mut registers = Registers::default();
+
+        self.instructions.iter().for_each(|instruction| {
+            (table.opcode(instruction).unwrap().execute)(&mut registers, &instruction.operands)
+        });
+
+        registers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics() {
+        let source = "noop\naddx\n\naddx five\nmulx 3\naddx 1";
+
+        assert_eq!(
+            Program::assemble(source, &InstructionTable::default())
+                .unwrap_err()
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "line 2: addx expects 1 operands, found 0",
+                "line 4: Cannot parse operand five of addx",
+                "line 5: Unknown instruction mulx",
+            ]
+        );
+    }
+
+    #[test]
+    fn static_information() {
+        let table = InstructionTable::default();
+        let program = Program::assemble("  noop\naddx   3\n\naddx -5  ", &table).unwrap();
+
+        assert_eq!(program.disassemble(), "noop\naddx 3\naddx -5");
+        assert_eq!(program.cycles(&table), 5);
+        assert_eq!(program.final_registers(&table)['x'], -1);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::day10::instruction::{Instruction, InstructionTable, Opcode};
+use crate::day10::registers::Registers;
+
+/// Conditions checked against the registers during every cycle
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Breakpoint {
+    Cycle(usize),
+    Value(char, i32),
+
+    // Stops whenever the register differs from the previous cycle
+    Watch(char),
+}
+
+impl Breakpoint {
+    fn hit(&self, cycle: usize, registers: &Registers, previous: &Registers) -> bool {
+        match *self {
+            Breakpoint::Cycle(at) => cycle == at,
+            Breakpoint::Value(name, value) => registers[name] == value,
+            Breakpoint::Watch(name) => registers[name] != previous[name],
+        }
+    }
+}
+
+/// Runs a program one cycle at a time.
+///
+/// Iterating yields the cycle number, starting from 1, together with
+/// the registers as they are during that cycle.
+#[derive(Debug, Clone)]
+pub(crate) struct Cpu {
+    opcodes: Vec<Opcode>,
+    program: Vec<Instruction>,
+    registers: Registers,
+
+    // Registers during the last cycle returned
+    during: Registers,
+    cycle: usize,
+    instruction_pointer: usize,
+    remaining_cycles: usize,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Cpu {
+    /// Fails if any instruction is unknown to `table` or has the wrong operands
+    pub(crate) fn new(program: Vec<Instruction>, table: &InstructionTable) -> Result<Self, String> {
+        let opcodes = program
+            .iter()
+            .map(|instruction| table.opcode(instruction).cloned())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            opcodes,
+            program,
+            registers: Registers::default(),
+            during: Registers::default(),
+            cycle: 0,
+            instruction_pointer: 0,
+            remaining_cycles: 0,
+            breakpoints: vec![],
+        })
+    }
+
+    pub(crate) fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Runs until a breakpoint is hit and returns the state of that cycle
+    /// and the breakpoint, or `None` once the program is over
+    pub(crate) fn resume(&mut self) -> Option<(usize, Registers, Breakpoint)> {
+        loop {
+            let previous = self.during.clone();
+            let (cycle, registers) = self.next()?;
+
+            if let Some(breakpoint) = self
+                .breakpoints
+                .iter()
+                .find(|breakpoint| breakpoint.hit(cycle, &registers, &previous))
+            {
+                return Some((cycle, registers, breakpoint.clone()));
+            }
+        }
+    }
+
+    /// One line per remaining cycle with the instruction being executed and the registers
+    pub(crate) fn trace(mut self) -> String {
+        let mut lines = vec![];
+
+        while self.instruction_pointer < self.program.len() {
+            let instruction = self.program[self.instruction_pointer].to_string();
+            let (cycle, registers) = self.next().unwrap();
+
+            lines.push(format!(
+                "{:>5} | {:<10} | {}",
+                cycle, instruction, registers
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl Iterator for Cpu {
+    type Item = (usize, Registers);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.instruction_pointer >= self.program.len() {
+            return None;
+        }
+
+        let opcode = &self.opcodes[self.instruction_pointer];
+
+        if self.remaining_cycles == 0 {
+            self.remaining_cycles = opcode.cycles;
+        }
+
+        self.cycle += 1;
+        self.remaining_cycles -= 1;
+        self.during = self.registers.clone();
+
+        // Instructions only take effect after their last cycle
+        if self.remaining_cycles == 0 {
+            (opcode.execute)(
+                &mut self.registers,
+                &self.program[self.instruction_pointer].operands,
+            );
+            self.instruction_pointer += 1;
+        }
+
+        Some((self.cycle, self.during.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn program(source: &str) -> Vec<Instruction> {
+        source
+            .lines()
+            .map(|line| Instruction::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn registers_during_cycles() {
+        let cpu = Cpu::new(
+            program("noop\naddx 3\naddx -5"),
+            &InstructionTable::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            cpu.map(|(cycle, registers)| (cycle, registers['x']))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]
+        );
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints() {
+        let mut cpu = Cpu::new(
+            program("noop\naddx 3\naddx -5\nnoop"),
+            &InstructionTable::default(),
+        )
+        .unwrap();
+
+        cpu.add_breakpoint(Breakpoint::Watch('x'));
+        cpu.add_breakpoint(Breakpoint::Value('x', -1));
+
+        assert_eq!(cpu.resume().map(|(cycle, _, _)| cycle), Some(4));
+        assert_eq!(
+            cpu.resume()
+                .map(|(cycle, _, breakpoint)| (cycle, breakpoint)),
+            Some((6, Breakpoint::Watch('x')))
+        );
+        assert_eq!(cpu.resume(), None);
+    }
+
+    #[test]
+    fn custom_instructions() {
+        let mut table = InstructionTable::default();
+        table
+            .register(
+                "addy",
+                Opcode {
+                    cycles: 3,
+                    operands: 1,
+                    execute: |registers, operands| registers.add('y', operands[0]),
+                },
+            )
+            .unwrap();
+
+        assert!(table
+            .register(
+                "free",
+                Opcode {
+                    cycles: 0,
+                    operands: 0,
+                    execute: |_, _| {},
+                },
+            )
+            .is_err());
+        assert!(Cpu::new(program("free"), &table).is_err());
+
+        assert!(Cpu::new(program("addy 2"), &InstructionTable::default()).is_err());
+        assert!(Cpu::new(program("addx 2 3"), &table).is_err());
+
+        let cpu = Cpu::new(program("addy 2\naddx 1"), &table).unwrap();
+
+        assert_eq!(
+            cpu.trace(),
+            "    1 | addy 2     | x=1\n    2 | addy 2     | x=1\n    3 | addy 2     | x=1\n    4 | addx 1     | x=1 y=2\n    5 | addx 1     | x=1 y=2"
+        );
+    }
+}
@@ -1,39 +1,120 @@
+use crate::day10::registers::Registers;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-pub(crate) enum Instruction {
-    Noop,
-    AddX(i32),
+/// What a mnemonic does: how many cycles it takes, how many operands it expects
+/// and its effect on the registers once the last cycle is over
+#[derive(Debug, Clone)]
+pub(crate) struct Opcode {
+    pub(crate) cycles: usize,
+    pub(crate) operands: usize,
+    pub(crate) execute: fn(&mut Registers, &[i32]),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct InstructionTable {
+    opcodes: HashMap<String, Opcode>,
+}
+
+impl Default for InstructionTable {
+    fn default() -> Self {
+        let mut table = Self {
+            opcodes: HashMap::new(),
+        };
+
+        [
+            (
+                "noop",
+                Opcode {
+                    cycles: 1,
+                    operands: 0,
+                    execute: |_, _| {},
+                },
+            ),
+            (
+                "addx",
+                Opcode {
+                    cycles: 2,
+                    operands: 1,
+                    execute: |registers, operands| registers.add('x', operands[0]),
+                },
+            ),
+        ]
+        .into_iter()
+        .for_each(|(mnemonic, opcode)| table.register(mnemonic, opcode).unwrap());
+
+        table
+    }
+}
+
+impl InstructionTable {
+    /// Fails if the opcode takes no cycle, as every instruction needs one to run
+    pub(crate) fn register(&mut self, mnemonic: &str, opcode: Opcode) -> Result<(), String> {
+        if opcode.cycles == 0 {
+            return Err(format!("{} must take at least one cycle", mnemonic));
+        }
+
+        self.opcodes.insert(mnemonic.to_owned(), opcode);
+
+        Ok(())
+    }
+
+    pub(crate) fn opcode(&self, instruction: &Instruction) -> Result<&Opcode, String> {
+        let opcode = self
+            .opcodes
+            .get(&instruction.mnemonic)
+            .ok_or_else(|| format!("Unknown instruction {}", instruction.mnemonic))?;
+
+        if opcode.operands != instruction.operands.len() {
+            return Err(format!(
+                "{} expects {} operands, found {}",
+                instruction.mnemonic,
+                opcode.operands,
+                instruction.operands.len()
+            ));
+        }
+
+        Ok(opcode)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Instruction {
+    pub(crate) mnemonic: String,
+    pub(crate) operands: Vec<i32>,
 }
 
 impl FromStr for Instruction {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens = s.split(" ").collect::<Vec<&str>>();
-
-        match tokens[0] {
-            "noop" => Ok(Self::Noop),
-            "addx" => match tokens.len() {
-                2 => Ok(Self::AddX(tokens[1].parse::<i32>().unwrap())),
-                _ => Err(format!("Cannot parse Addx from {}", s)),
-            },
-            _ => Err(format!("Cannot anything from {}", s)),
-        }
+        let mut tokens = s.split_whitespace();
+        let mnemonic = tokens
+            .next()
+            .ok_or_else(|| format!("Cannot parse anything from {}", s))?;
+
+        Ok(Self {
+            mnemonic: mnemonic.to_owned(),
+            operands: tokens
+                .map(|token| {
+                    token
+                        .parse::<i32>()
+                        .map_err(|_| format!("Cannot parse operand {} of {}", token, mnemonic))
+                })
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
-impl Instruction {
-    pub(crate) fn register_increment(&self, register: i32) -> i32 {
-        match self {
-            Self::Noop => register,
-            Self::AddX(val) => register + *val,
-        }
-    }
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.mnemonic)?;
 
-    pub(crate) fn cycle_increment(&self, cycle: usize) -> usize {
-        match self {
-            Self::Noop => cycle + 1,
-            Self::AddX(_) => cycle + 2,
+        for operand in self.operands.iter() {
+            write!(f, " {}", operand)?;
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Index;
+
+/// Named registers, `x` starts at 1 and any other register at 0
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Registers(BTreeMap<char, i32>);
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self(BTreeMap::from([('x', 1)]))
+    }
+}
+
+impl Registers {
+    pub(crate) fn add(&mut self, name: char, value: i32) {
+        *self.0.entry(name).or_default() += value;
+    }
+}
+
+impl Index<char> for Registers {
+    type Output = i32;
+
+    fn index(&self, name: char) -> &Self::Output {
+        self.0.get(&name).unwrap_or(&0)
+    }
+}
+
+impl Display for Registers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+}
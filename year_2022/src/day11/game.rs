@@ -1,14 +1,18 @@
+use crate::day11::item::WorryLevel;
 use crate::day11::monkey::Monkey;
+use crate::number_theory::lcm;
 use priority_queue::PriorityQueue;
 use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::str::FromStr;
 
 pub(crate) struct Destination {
     destination: usize,
-    stress_level: usize,
+    stress_level: WorryLevel,
 }
 
 impl Destination {
-    pub(crate) fn new(destination: usize, stress_level: usize) -> Self {
+    pub(crate) fn new(destination: usize, stress_level: WorryLevel) -> Self {
         Self {
             destination,
             stress_level,
@@ -19,20 +23,100 @@ impl Destination {
 pub(crate) struct Game {
     monkeys: Vec<Monkey>,
     stats: Vec<usize>,
+
+    // Smallest common multiple of all monkeys' divisible number
+    modulus: WorryLevel,
+
+    // First monkey whose operation divides, if any
+    divider: Option<usize>,
+}
+
+impl FromStr for Game {
+    type Err = String;
+
+    /// Reads every `Monkey <n>:` block, blocks being separated by an empty line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let monkeys = s
+            .split("\n\n")
+            .filter(|block| !block.trim().is_empty())
+            .enumerate()
+            .map(|(i, block)| {
+                let (header, rest) = block.trim_start().split_once('\n').unwrap_or((block, ""));
+
+                if header.trim_end() != format!("Monkey {}:", i) {
+                    return Err(format!("Expected `Monkey {}:`, got `{}`", i, header));
+                }
+
+                Monkey::from_str(rest)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(monkeys)
+    }
 }
 
 impl Game {
-    pub(crate) fn new(monkeys: Vec<Monkey>) -> Self {
+    pub(crate) fn new(monkeys: Vec<Monkey>) -> Result<Self, String> {
+        if monkeys.is_empty() {
+            return Err("There are no monkeys".to_owned());
+        }
+
+        for (i, monkey) in monkeys.iter().enumerate() {
+            if let Some(destination) = monkey
+                .destinations()
+                .into_iter()
+                .find(|destination| *destination >= monkeys.len())
+            {
+                return Err(format!(
+                    "Monkey {} throws to monkey {}, but there are only {} monkeys",
+                    i,
+                    destination,
+                    monkeys.len()
+                ));
+            }
+        }
+
+        let modulus = monkeys
+            .iter()
+            .fold(1, |modulus, monkey| lcm(modulus, monkey.divisor()));
         let stats = vec![0; monkeys.len()];
+        let divider = monkeys.iter().position(Monkey::divides);
 
-        Self { monkeys, stats }
+        Ok(Self {
+            monkeys,
+            stats,
+            modulus,
+            divider,
+        })
     }
 
-    /// `modulo_reducer` is used to keep numbers small
-    /// is the smallest common multiple of all monkeys' divisible number
-    pub(crate) fn round_step(&mut self, worry_level: usize, modulo_reducer: usize) {
+    /// Fails if worry levels cannot be kept modulo `modulus`, as no reduction survives
+    /// a division
+    fn check_reducible(&self) -> Result<(), String> {
+        match self.divider {
+            Some(monkey) => Err(format!(
+                "Monkey {} divides worry levels, so they cannot be kept small without relief",
+                monkey
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Plays one round, dividing worry levels by `worry_level` after each inspection.
+    ///
+    /// Without relief worry levels are kept modulo `modulus`, which keeps every
+    /// divisibility test intact, so this fails if an operation divides. It also
+    /// fails if an operation divides by zero or overflows.
+    pub(crate) fn round_step(&mut self, worry_level: WorryLevel) -> Result<(), String> {
+        let modulus = if worry_level == 1 {
+            self.check_reducible()?;
+            Some(self.modulus)
+        } else {
+            None
+        };
+
         for i in 0..self.monkeys.len() {
-            if let Some(destinations) = self.monkeys[i].round_step(worry_level, modulo_reducer) {
+            if let Some(destinations) = self.monkeys[i].round_step(worry_level, modulus)? {
                 self.stats[i] += destinations.len();
 
                 destinations.into_iter().for_each(|destination| {
@@ -41,6 +125,84 @@ impl Game {
                 });
             }
         }
+
+        Ok(())
+    }
+
+    /// Plays a single round for one item without relief, starting from `holder`.
+    ///
+    /// Items thrown to a monkey that comes later in the round are inspected again
+    /// in the same round. Returns where the item waits for the next round and
+    /// the monkeys that inspected it.
+    fn item_round(
+        &self,
+        mut holder: usize,
+        mut stress_level: WorryLevel,
+    ) -> Result<((usize, WorryLevel), Vec<usize>), String> {
+        let mut inspected_by = vec![];
+
+        loop {
+            inspected_by.push(holder);
+            let destination = self.monkeys[holder].inspect(stress_level, 1, Some(self.modulus))?;
+
+            if destination.destination <= holder {
+                return Ok((
+                    (destination.destination, destination.stress_level),
+                    inspected_by,
+                ));
+            }
+
+            holder = destination.destination;
+            stress_level = destination.stress_level;
+        }
+    }
+
+    /// Number of inspections of each monkey after `rounds` rounds without relief.
+    ///
+    /// Items never interact with each other, so each one is followed on its own.
+    /// The monkey holding it and its worry level modulo `modulus` at the start
+    /// of a round can only take finitely many values, so once a state repeats
+    /// the remaining rounds are accounted for in closed form. This fails if an
+    /// operation divides, as worry levels then cannot be reduced.
+    pub(crate) fn inspections(&self, rounds: u64) -> Result<Vec<u64>, String> {
+        self.check_reducible()?;
+
+        let mut result = vec![0; self.monkeys.len()];
+
+        for (holder, monkey) in self.monkeys.iter().enumerate() {
+            for stress_level in monkey.items() {
+                let mut state = (holder, stress_level.rem_euclid(self.modulus));
+                let mut seen = HashMap::new();
+                let mut history: Vec<Vec<usize>> = vec![];
+                let mut round = 0;
+
+                while round < rounds {
+                    if let Some(cycle_start) = seen.insert(state, round) {
+                        let cycle = &history[cycle_start as usize..];
+                        let remaining = rounds - round;
+                        let full_cycles = remaining / cycle.len() as u64;
+
+                        cycle.iter().enumerate().for_each(|(i, inspected_by)| {
+                            let times =
+                                full_cycles + ((i as u64) < remaining % cycle.len() as u64) as u64;
+                            inspected_by
+                                .iter()
+                                .for_each(|monkey| result[*monkey] += times);
+                        });
+
+                        break;
+                    }
+
+                    let (next_state, inspected_by) = self.item_round(state.0, state.1)?;
+                    inspected_by.iter().for_each(|monkey| result[*monkey] += 1);
+                    history.push(inspected_by);
+                    state = next_state;
+                    round += 1;
+                }
+            }
+        }
+
+        Ok(result)
     }
 
     pub(crate) fn most_active_monkeys(&self, n: usize) -> Vec<usize> {
@@ -56,3 +218,161 @@ impl Game {
         queue.into_iter().map(|(e, _priority)| e).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::read_to_string;
+
+    fn monkey_business(worry_level: WorryLevel, rounds: usize) -> usize {
+        let mut game = Game::from_str(&read_to_string("src/day11/test").unwrap()).unwrap();
+
+        (0..rounds).for_each(|_| game.round_step(worry_level).unwrap());
+        game.most_active_monkeys(2).into_iter().product()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(monkey_business(3, 20), 10_605);
+        assert_eq!(monkey_business(1, 10_000), 2_713_310_158);
+    }
+
+    #[test]
+    fn inspections_in_closed_form() {
+        let mut game = Game::from_str(&read_to_string("src/day11/test").unwrap()).unwrap();
+        let inspections = game.inspections(10_000).unwrap();
+
+        (0..10_000).for_each(|_| game.round_step(1).unwrap());
+
+        assert_eq!(inspections, vec![52_166, 47_830, 1_938, 52_013]);
+        assert_eq!(
+            inspections,
+            game.stats.iter().map(|n| *n as u64).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn inspections_after_many_rounds() {
+        // Modulo 6, the item of monkey 0 goes 0, 1, 2, 3 -> monkey 1 -> 4, 5, 6 -> monkey 1
+        // -> 1, one round per step, so monkey 1 inspects it in every odd round but the
+        // first. The other item goes 1 -> monkey 0 -> 2 in the first round, then follows
+        // the same path, reaching monkey 1 in every even round.
+        let mut game = Game::from_str(
+            "Monkey 0:
+  Starting items: 0
+  Operation: new = old + 1
+  Test: divisible by 3
+    If true: throw to monkey 1
+    If false: throw to monkey 0
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+",
+        )
+        .unwrap();
+
+        let rounds = 1_000_000_000_000;
+        assert_eq!(game.inspections(rounds), Ok(vec![2 * rounds - 1, rounds]));
+
+        let inspections = game.inspections(1_001).unwrap();
+        (0..1_001).for_each(|_| game.round_step(1).unwrap());
+
+        assert_eq!(inspections, vec![2_001, 1_001]);
+        assert_eq!(
+            inspections,
+            game.stats.iter().map(|n| *n as u64).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reduced_worry_levels() {
+        let monkeys = |operation: &str| {
+            format!(
+                "Monkey 0:
+  Starting items: 3, 10
+  Operation: new = {}
+  Test: divisible by 5
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 4
+  Operation: new = old * 3
+  Test: divisible by 7
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+",
+                operation
+            )
+        };
+
+        // Worry levels go below zero right away, and are handled the same whether they
+        // are reduced modulo 35 or not
+        let mut game = Game::from_str(&monkeys("old - 7")).unwrap();
+
+        for monkey in &game.monkeys {
+            for stress_level in -50..50 {
+                let exact = monkey.inspect(stress_level, 1, None).unwrap();
+                let reduced = monkey.inspect(stress_level, 1, Some(35)).unwrap();
+
+                assert_eq!(exact.destination, reduced.destination);
+                assert_eq!(exact.stress_level.rem_euclid(35), reduced.stress_level);
+            }
+        }
+
+        let inspections = game.inspections(100).unwrap();
+        (0..100).for_each(|_| game.round_step(1).unwrap());
+
+        assert_eq!(
+            inspections,
+            game.stats.iter().map(|n| *n as u64).collect::<Vec<_>>()
+        );
+
+        let mut game = Game::from_str(&monkeys("old / 2")).unwrap();
+
+        assert!(game.inspections(100).is_err());
+        assert!(game.round_step(1).is_err());
+        assert!(game.round_step(3).is_ok());
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let mut game = Game::from_str(
+            "Monkey 0:
+  Starting items: 3
+  Operation: new = old / (old - old)
+  Test: divisible by 5
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            game.round_step(3),
+            Err("Division by zero in `(old / (old - old))`".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_destination() {
+        let game = Game::from_str(
+            "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+",
+        );
+
+        assert_eq!(
+            game.err(),
+            Some("Monkey 0 throws to monkey 1, but there are only 1 monkeys".to_owned())
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use std::collections::VecDeque;
 use std::str::FromStr;
 
+/// Worry levels are signed, so that a subtraction means the same with or without
+/// reducing them modulo the monkeys' divisors
+pub(crate) type WorryLevel = i64;
+
 #[derive(Debug)]
 pub(crate) struct StartingItems {
-    items: VecDeque<usize>,
+    items: VecDeque<WorryLevel>,
 }
 
 impl FromStr for StartingItems {
@@ -12,36 +16,42 @@ impl FromStr for StartingItems {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let tokens = s.trim_start().split(":").collect::<Vec<&str>>();
 
-        if tokens[0] != "Starting items" {
+        if tokens.len() != 2 || tokens[0] != "Starting items" {
             return Err(format!("String `{}` does not have first part right", s));
         }
 
-        let tokens = tokens[1].trim_start().split(',').collect::<Vec<&str>>();
+        // A monkey may start with no item at all
+        if tokens[1].trim().is_empty() {
+            return Ok(Self {
+                items: VecDeque::new(),
+            });
+        }
 
-        return Ok(Self {
-            items: tokens
-                .into_iter()
+        Ok(Self {
+            items: tokens[1]
+                .split(',')
                 .map(|item_worry_level| {
                     item_worry_level
-                        .trim_start()
-                        .parse::<usize>()
-                        .expect(&format!("Expected number. Got `{}`", item_worry_level))
+                        .trim()
+                        .parse::<WorryLevel>()
+                        .map_err(|_| format!("Expected number. Got `{}`", item_worry_level))
                 })
-                .collect(),
-        });
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
 impl StartingItems {
-    pub(crate) fn push_back(&mut self, value: usize) {
+    pub(crate) fn push_back(&mut self, value: WorryLevel) {
         self.items.push_back(value)
     }
 
-    pub(crate) fn pop_front(&mut self) -> Option<usize> {
+    pub(crate) fn pop_front(&mut self) -> Option<WorryLevel> {
         self.items.pop_front()
     }
-    pub(crate) fn is_empty(&self) -> bool {
-        self.items.is_empty()
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &WorryLevel> {
+        self.items.iter()
     }
 }
 
@@ -54,4 +64,21 @@ mod tests {
         let starting_items = StartingItems::from_str("  Starting items: 98, 89, 52").unwrap();
         assert_eq!(starting_items.items, vec![98, 89, 52]);
     }
+
+    #[test]
+    fn parse_empty() {
+        let starting_items = StartingItems::from_str("  Starting items:").unwrap();
+        assert!(starting_items.items.is_empty());
+
+        let starting_items = StartingItems::from_str("  Starting items:  ").unwrap();
+        assert!(starting_items.items.is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(StartingItems::from_str("  Starting items: 98, , 52").is_err());
+        assert!(StartingItems::from_str("  Starting items: 98, x").is_err());
+        assert!(StartingItems::from_str("  Starting items: 98,").is_err());
+        assert!(StartingItems::from_str("  Starting things: 98").is_err());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::day11::game::Destination;
-use crate::day11::item::StartingItems;
+use crate::day11::item::{StartingItems, WorryLevel};
 use crate::day11::operation::Play;
-use crate::day11::test::Action;
+use crate::day11::test::{Action, Test};
+use std::str::FromStr;
 
 pub(crate) struct Monkey {
     starting_items: StartingItems,
@@ -9,7 +10,48 @@ pub(crate) struct Monkey {
     action: Action,
 }
 
+impl FromStr for Monkey {
+    type Err = String;
+
+    /// Parses the lines following `Monkey <n>:`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().map(str::trim_end).collect::<Vec<_>>();
+
+        if lines.len() != 5 {
+            return Err(format!("Expected 5 lines, got {}:\n{}", lines.len(), s));
+        }
+
+        let starting_items = StartingItems::from_str(lines[0])?;
+        let play = Play::from_str(lines[1])?;
+        let test = Test::from_str(lines[2])?;
+        let on_true_destination = Self::destination(lines[3], "If true")?;
+        let on_false_destination = Self::destination(lines[4], "If false")?;
+
+        Ok(Self::new(
+            starting_items,
+            play,
+            Action(test, on_true_destination, on_false_destination),
+        ))
+    }
+}
+
 impl Monkey {
+    fn destination(line: &str, condition: &str) -> Result<usize, String> {
+        line.trim_start()
+            .strip_prefix(condition)
+            .and_then(|rest| rest.strip_prefix(": throw to monkey "))
+            .and_then(|destination| destination.parse::<usize>().ok())
+            .ok_or_else(|| format!("`{}` is not a valid `{}` line", line, condition))
+    }
+
+    pub(crate) fn destinations(&self) -> [usize; 2] {
+        [self.action.1, self.action.2]
+    }
+
+    pub(crate) fn divisor(&self) -> WorryLevel {
+        self.action.divisor()
+    }
+
     pub(crate) fn new(starting_items: StartingItems, play: Play, action: Action) -> Self {
         Self {
             starting_items,
@@ -18,37 +60,54 @@ impl Monkey {
         }
     }
 
-    fn play(&self, item: usize) -> usize {
-        self.play.apply(item)
+    pub(crate) fn divides(&self) -> bool {
+        self.play.has_division()
     }
 
-    fn get_bored(val: usize, worry_level: usize) -> usize {
-        val / worry_level
+    fn get_bored(val: WorryLevel, worry_level: WorryLevel) -> WorryLevel {
+        val.div_euclid(worry_level)
     }
 
     pub(crate) fn round_step(
         &mut self,
-        worry_level: usize,
-        modulo_reducer: usize,
-    ) -> Option<Vec<Destination>> {
+        worry_level: WorryLevel,
+        modulus: Option<WorryLevel>,
+    ) -> Result<Option<Vec<Destination>>, String> {
         let mut result = vec![];
 
-        while !self.starting_items.is_empty() {
-            let stress_level = self.starting_items.pop_front().unwrap();
-            let new_stress_level =
-                Self::get_bored(self.play(stress_level), worry_level) % modulo_reducer;
-
-            if (self.action)(new_stress_level) {
-                result.push(Destination::new(self.action.1, new_stress_level));
-            } else {
-                result.push(Destination::new(self.action.2, new_stress_level));
-            }
+        while let Some(stress_level) = self.starting_items.pop_front() {
+            result.push(self.inspect(stress_level, worry_level, modulus)?);
         }
 
-        (!result.is_empty()).then_some(result)
+        Ok((!result.is_empty()).then_some(result))
+    }
+
+    /// Where a single item goes and how worrying it is after this monkey inspects it.
+    ///
+    /// With a `modulus`, the worry level is only known modulo it, so there is no relief.
+    pub(crate) fn inspect(
+        &self,
+        stress_level: WorryLevel,
+        worry_level: WorryLevel,
+        modulus: Option<WorryLevel>,
+    ) -> Result<Destination, String> {
+        let new_stress_level = match modulus {
+            None => Self::get_bored(self.play.apply(stress_level, None)?, worry_level),
+            Some(_) => self.play.apply(stress_level, modulus)?,
+        };
+
+        Ok(if self.action.matches(new_stress_level) {
+            Destination::new(self.action.1, new_stress_level)
+        } else {
+            Destination::new(self.action.2, new_stress_level)
+        })
+    }
+
+    pub(crate) fn items(&self) -> impl Iterator<Item = &WorryLevel> {
+        self.starting_items.iter()
     }
 
-    pub(crate) fn push_back(&mut self, val: usize) {
+    pub(crate) fn push_back(&mut self, val: WorryLevel) {
         self.starting_items.push_back(val);
     }
 }
@@ -1,10 +1,191 @@
+use crate::day11::item::WorryLevel;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
 use std::str::FromStr;
 
+/// Right hand side of `new = <expression>`
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) enum Play {
-    Add(usize),
-    Sub(usize),
-    Mul(usize),
-    Pow(u32),
+    Old,
+    Literal(WorryLevel),
+    Add(Box<Play>, Box<Play>),
+    Sub(Box<Play>, Box<Play>),
+    Mul(Box<Play>, Box<Play>),
+    Div(Box<Play>, Box<Play>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Old,
+    Literal(WorryLevel),
+    Operator(char),
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {}
+            '+' | '-' | '*' | '/' => tokens.push(Token::Operator(c)),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '0'..='9' => {
+                let mut number = c.to_string();
+
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    number.push(digit);
+                }
+
+                tokens.push(Token::Literal(number.parse().map_err(|_| {
+                    format!("Number `{}` is too large in `{}`", number, s)
+                })?));
+            }
+            _ if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+
+                while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+                    word.push(letter);
+                }
+
+                if word != "old" {
+                    return Err(format!("Unknown variable `{}` in `{}`", word, s));
+                }
+
+                tokens.push(Token::Old);
+            }
+            _ => return Err(format!("Unexpected `{}` in `{}`", c, s)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+impl Play {
+    /// expression := term (('+' | '-') term)*
+    fn expression(tokens: &mut Tokens) -> Result<Self, String> {
+        let mut result = Self::term(tokens)?;
+
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = tokens.peek().cloned() {
+            tokens.next();
+            let rhs = Box::new(Self::term(tokens)?);
+
+            result = match operator {
+                '+' => Self::Add(Box::new(result), rhs),
+                _ => Self::Sub(Box::new(result), rhs),
+            };
+        }
+
+        Ok(result)
+    }
+
+    /// term := factor (('*' | '/') factor)*
+    fn term(tokens: &mut Tokens) -> Result<Self, String> {
+        let mut result = Self::factor(tokens)?;
+
+        while let Some(Token::Operator(operator @ ('*' | '/'))) = tokens.peek().cloned() {
+            tokens.next();
+            let rhs = Box::new(Self::factor(tokens)?);
+
+            result = match operator {
+                '*' => Self::Mul(Box::new(result), rhs),
+                _ if *rhs == Self::Literal(0) => {
+                    return Err(format!("Division by zero in `{} / 0`", result))
+                }
+                _ => Self::Div(Box::new(result), rhs),
+            };
+        }
+
+        Ok(result)
+    }
+
+    /// factor := 'old' | number | '(' expression ')'
+    fn factor(tokens: &mut Tokens) -> Result<Self, String> {
+        match tokens.next() {
+            Some(Token::Old) => Ok(Self::Old),
+            Some(Token::Literal(value)) => Ok(Self::Literal(value)),
+            Some(Token::Open) => {
+                let result = Self::expression(tokens)?;
+
+                match tokens.next() {
+                    Some(Token::Close) => Ok(result),
+                    token => Err(format!("Expected `)`, got {:?}", token)),
+                }
+            }
+            token => Err(format!("Expected `old`, a number or `(`, got {:?}", token)),
+        }
+    }
+
+    /// Whether the expression divides, which no reduction of the worry levels survives
+    pub(crate) fn has_division(&self) -> bool {
+        match self {
+            Self::Old | Self::Literal(_) => false,
+            Self::Add(lhs, rhs) | Self::Sub(lhs, rhs) | Self::Mul(lhs, rhs) => {
+                lhs.has_division() || rhs.has_division()
+            }
+            Self::Div(_, _) => true,
+        }
+    }
+
+    /// Value of the expression for `old = val`.
+    ///
+    /// Worry levels are signed, so a subtraction may go below zero. With a modulus, the
+    /// result is the same value taken modulo `modulus`, which is only meaningful without
+    /// division. Fails on a division by zero or when the worry level overflows.
+    pub(crate) fn apply(
+        &self,
+        val: WorryLevel,
+        modulus: Option<WorryLevel>,
+    ) -> Result<WorryLevel, String> {
+        let overflow = || format!("Worry level overflows in `{}`", self);
+
+        match (self, modulus) {
+            (Self::Old, None) => Ok(val),
+            (Self::Old, Some(modulus)) => Ok(val.rem_euclid(modulus)),
+            (Self::Literal(value), None) => Ok(*value),
+            (Self::Literal(value), Some(modulus)) => Ok(value.rem_euclid(modulus)),
+            (Self::Add(lhs, rhs), None) => lhs
+                .apply(val, None)?
+                .checked_add(rhs.apply(val, None)?)
+                .ok_or_else(overflow),
+            (Self::Sub(lhs, rhs), None) => lhs
+                .apply(val, None)?
+                .checked_sub(rhs.apply(val, None)?)
+                .ok_or_else(overflow),
+            (Self::Mul(lhs, rhs), None) => lhs
+                .apply(val, None)?
+                .checked_mul(rhs.apply(val, None)?)
+                .ok_or_else(overflow),
+            (Self::Div(lhs, rhs), None) => match rhs.apply(val, None)? {
+                0 => Err(format!("Division by zero in `{}`", self)),
+                rhs => lhs
+                    .apply(val, None)?
+                    .checked_div_euclid(rhs)
+                    .ok_or_else(overflow),
+            },
+            (Self::Add(lhs, rhs), Some(modulus)) => Ok((lhs.apply(val, modulus.into())? as i128
+                + rhs.apply(val, modulus.into())? as i128)
+                .rem_euclid(modulus as i128)
+                as WorryLevel),
+            // Both operands are reduced, so their difference cannot overflow
+            (Self::Sub(lhs, rhs), Some(modulus)) => Ok((lhs.apply(val, modulus.into())?
+                - rhs.apply(val, modulus.into())?)
+            .rem_euclid(modulus)),
+            (Self::Mul(lhs, rhs), Some(modulus)) => Ok((lhs.apply(val, modulus.into())? as i128
+                * rhs.apply(val, modulus.into())? as i128)
+                .rem_euclid(modulus as i128)
+                as WorryLevel),
+            (Self::Div(_, _), Some(_)) => Err(format!(
+                "Worry levels cannot be reduced through the division in `{}`",
+                self
+            )),
+        }
+    }
 }
 
 impl FromStr for Play {
@@ -13,31 +194,33 @@ impl FromStr for Play {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let tokens = s.trim_start().split(':').collect::<Vec<&str>>();
 
-        if tokens[0] != "Operation" {
+        if tokens[0] != "Operation" || tokens.len() != 2 {
             return Err(format!("String `{}` does not have first part right", s));
         }
 
-        let tokens = tokens[1].trim_start().split(' ').collect::<Vec<&str>>();
+        let expression = tokens[1]
+            .trim_start()
+            .strip_prefix("new =")
+            .ok_or_else(|| format!("Expected `new = ` in `{}`", s))?;
+        let mut tokens = tokenize(expression)?.into_iter().peekable();
+        let play = Self::expression(&mut tokens)?;
 
-        match tokens[3] {
-            "+" => Ok(Self::Add(tokens[4].parse::<usize>().unwrap())),
-            "-" => Ok(Self::Sub(tokens[4].parse::<usize>().unwrap())),
-            "*" => match tokens[4] {
-                "old" => Ok(Self::Pow(2)),
-                _ => Ok(Self::Mul(tokens[4].parse::<usize>().unwrap())),
-            },
-            _ => Err(format!("Cannot parse any command from {}", s)),
+        match tokens.next() {
+            None => Ok(play),
+            Some(token) => Err(format!("Unexpected {:?} in `{}`", token, s)),
         }
     }
 }
 
-impl Play {
-    pub(crate) fn apply(&self, val: usize) -> usize {
-        match *self {
-            Self::Mul(m) => val * m as usize,
-            Self::Pow(p) => usize::pow(val, p as u32),
-            Self::Add(a) => val + a as usize,
-            Self::Sub(s) => val - s as usize,
+impl Display for Play {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Old => write!(f, "old"),
+            Self::Literal(value) => write!(f, "{}", value),
+            Self::Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
+            Self::Sub(lhs, rhs) => write!(f, "({} - {})", lhs, rhs),
+            Self::Mul(lhs, rhs) => write!(f, "({} * {})", lhs, rhs),
+            Self::Div(lhs, rhs) => write!(f, "({} / {})", lhs, rhs),
         }
     }
 }
@@ -50,24 +233,94 @@ mod tests {
     #[test]
     fn parse_mul() {
         let operation = Play::from_str("  Operation: new = old * 2").unwrap();
-        assert_eq!(operation.apply(4), 8);
+        assert_eq!(operation.apply(4, None), Ok(8));
     }
 
     #[test]
     fn parse_pow() {
         let operation = Play::from_str("  Operation: new = old * old").unwrap();
-        assert_eq!(operation.apply(3), 9);
+        assert_eq!(operation.apply(3, None), Ok(9));
     }
 
     #[test]
     fn parse_add() {
         let operation = Play::from_str("  Operation: new = old + 2").unwrap();
-        assert_eq!(operation.apply(3), 5);
+        assert_eq!(operation.apply(3, None), Ok(5));
     }
 
     #[test]
     fn parse_sub() {
         let operation = Play::from_str("  Operation: new = old - 2").unwrap();
-        assert_eq!(operation.apply(3), 1);
+        assert_eq!(operation.apply(3, None), Ok(1));
+    }
+
+    #[test]
+    fn parse_expression() {
+        let operation = Play::from_str("  Operation: new = 3 + old * (old + 1) / 2").unwrap();
+        assert_eq!(operation.to_string(), "(3 + ((old * (old + 1)) / 2))");
+        assert_eq!(operation.apply(4, None), Ok(13));
+
+        let operation = Play::from_str("  Operation: new = old + old").unwrap();
+        assert_eq!(operation.apply(4, None), Ok(8));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Play::from_str("  Operation: new = old ^ 2").is_err());
+        assert!(Play::from_str("  Operation: new = (old + 2").is_err());
+        assert!(Play::from_str("  Operation: new = older + 2").is_err());
+        assert!(Play::from_str("  Operation: new = old 2").is_err());
+        assert!(Play::from_str("  Operation: new = old / 0").is_err());
+    }
+
+    #[test]
+    fn apply_errors() {
+        // Only known to divide by zero once `old` is known
+        let operation = Play::from_str("  Operation: new = old / (old - old)").unwrap();
+
+        assert_eq!(
+            operation.apply(3, None),
+            Err("Division by zero in `(old / (old - old))`".to_owned())
+        );
+        assert!(operation.apply(3, Some(5)).is_err());
+
+        let operation = Play::from_str("  Operation: new = old * old").unwrap();
+
+        assert_eq!(
+            operation.apply(WorryLevel::MAX, None),
+            Err("Worry level overflows in `(old * old)`".to_owned())
+        );
+        assert_eq!(operation.apply(WorryLevel::MAX, Some(10)), Ok(9));
+    }
+
+    #[test]
+    fn apply_modulo() {
+        let operation = Play::from_str("  Operation: new = old * old + 20 - 7").unwrap();
+
+        assert!(!operation.has_division());
+        assert_eq!(operation.apply(2, None), Ok(17));
+        assert_eq!(operation.apply(2, Some(5)), Ok(2));
+
+        // `2 - 7` is -5, a multiple of 5, whether or not it is reduced
+        let operation = Play::from_str("  Operation: new = old - 7").unwrap();
+
+        assert_eq!(operation.apply(2, None), Ok(-5));
+        assert_eq!(operation.apply(2, Some(5)), Ok(0));
+        assert_eq!(operation.apply(2, Some(6)), Ok(1));
+
+        let operation = Play::from_str("  Operation: new = (old - 20) * old + 3").unwrap();
+
+        for val in -40..40 {
+            for modulus in [5, 6, 35] {
+                assert_eq!(
+                    operation.apply(val, Some(modulus)),
+                    Ok(operation.apply(val, None).unwrap().rem_euclid(modulus))
+                );
+            }
+        }
+
+        assert!(Play::from_str("  Operation: new = (old + 1) / 2")
+            .unwrap()
+            .has_division());
     }
 }
@@ -1,9 +1,12 @@
+use crate::day11::item::WorryLevel;
 use std::ops::Deref;
 
 use std::str::FromStr;
 
-pub(crate) struct Test {
-    test: Box<dyn Fn(usize) -> bool>,
+/// Predicate a monkey checks to decide where to throw an item
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Test {
+    DivisibleBy(WorryLevel),
 }
 
 impl FromStr for Test {
@@ -12,37 +15,36 @@ impl FromStr for Test {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let tokens = s.trim_start().split(':').collect::<Vec<&str>>();
 
-        if tokens.is_empty() {
-            return Err(format!("`{}` yielded empty tokens", s));
-        }
-
-        if tokens[0] != "Test" {
-            return Err(format!("`{}` is not a valid test1 command", s));
+        if tokens.len() != 2 || tokens[0] != "Test" {
+            return Err(format!("`{}` is not a valid test command", s));
         }
 
         let tokens = tokens[1].trim_start().split(' ').collect::<Vec<&str>>();
 
-        match tokens[0] {
-            "divisible" => {
-                if tokens[1] != "by" {
-                    return Err(format!("Invalid `divisible by` test1 from `{}`", s));
-                }
-
-                let d = tokens[2].parse::<usize>().unwrap();
-                return Ok(Self {
-                    test: Box::new(move |val| val % d == 0),
-                });
-            }
-            _ => Err(format!("Invalid test1 `{}`", s)),
+        match tokens[..] {
+            ["divisible", "by", divisor] => divisor
+                .parse::<WorryLevel>()
+                .ok()
+                .filter(|divisor| *divisor > 0)
+                .map(Self::DivisibleBy)
+                .ok_or_else(|| format!("Invalid divisor in `{}`", s)),
+            _ => Err(format!("Invalid test `{}`", s)),
         }
     }
 }
 
-impl Deref for Test {
-    type Target = Box<dyn Fn(usize) -> bool>;
+impl Test {
+    pub(crate) fn matches(&self, val: WorryLevel) -> bool {
+        match *self {
+            Self::DivisibleBy(divisor) => val.rem_euclid(divisor) == 0,
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.test
+    /// Number worry levels can be reduced by without changing the outcome of the test
+    pub(crate) fn divisor(&self) -> WorryLevel {
+        match *self {
+            Self::DivisibleBy(divisor) => divisor,
+        }
     }
 }
 
@@ -63,12 +65,15 @@ mod tests {
     #[test]
     fn parse_test() {
         let test = Test::from_str("  Test: divisible by 13").unwrap();
-        assert!(test(13));
+        assert!(test.matches(13));
+        assert!(test.matches(-26));
+        assert!(!test.matches(-1));
+        assert_eq!(test.divisor(), 13);
     }
 
     #[test]
     fn parse_action() {
         let action = Action(Test::from_str("  Test: divisible by 13").unwrap(), 0, 2);
-        assert!(action(13));
+        assert!(action.matches(13));
     }
 }
@@ -1,15 +1,26 @@
+use crate::day13::external::{ExternalSorter, PacketReader};
 use crate::day13::packet::Packet;
 use std::cmp::Ordering;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
 
+mod external;
 mod packet;
 
 pub struct Day13;
 
 impl Day13 {
     pub fn part_one() -> usize {
+        Self::ordered_pairs(false)
+    }
+
+    /// Same as `part_one`, printing why each pair is ordered the way it is
+    pub fn part_one_explained() -> usize {
+        Self::ordered_pairs(true)
+    }
+
+    fn ordered_pairs(explain: bool) -> usize {
         let buffer_reader = BufReader::new(File::open("src/day13/input").unwrap());
 
         buffer_reader
@@ -21,11 +32,16 @@ impl Day13 {
             .chunks(2)
             .enumerate()
             .filter_map(|(i, chunk)| {
-                (Packet::from_str(&chunk[0])
-                    .unwrap()
-                    .partial_cmp(&Packet::from_str(&chunk[1]).unwrap())
-                    == Some(Ordering::Less))
-                .then_some(i + 1)
+                let left = Packet::from_str(&chunk[0]).unwrap();
+                let right = Packet::from_str(&chunk[1]).unwrap();
+
+                if explain {
+                    println!("== Pair {} ==", i + 1);
+                    println!("{}\n{}", left, right);
+                    println!("{}\n", left.compare_explained(&right));
+                }
+
+                (left.cmp(&right) == Ordering::Less).then_some(i + 1)
             })
             .sum()
     }
@@ -34,10 +50,7 @@ impl Day13 {
         let buffer_reader = BufReader::new(File::open("src/day13/input").unwrap());
         let mut raw_signal = buffer_reader
             .lines()
-            .filter_map(|line| match Packet::from_str(line.as_ref().unwrap()) {
-                Ok(packet) => Some(packet),
-                Err(_) => None,
-            })
+            .filter_map(|line| Packet::from_str(line.as_ref().unwrap()).ok())
             .collect::<Vec<_>>();
         let divider_packets = vec![
             Packet::from_str("[[2]]").unwrap(),
@@ -53,4 +66,30 @@ impl Day13 {
             .filter_map(|(i, signal)| (divider_packets.contains(&signal)).then_some(i + 1))
             .product()
     }
+
+    /// Same as `part_two`, for packet files that do not fit in memory.
+    ///
+    /// Packets are parsed lazily and sorted in runs of at most `run_size` packets
+    /// kept in temporary files, which are then merged `fan_in` at a time.
+    pub fn part_two_streaming(path: &str, run_size: usize, fan_in: usize) -> Result<usize, String> {
+        let file = File::open(path).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+        let divider_packets = [
+            Packet::from_str("[[2]]").unwrap(),
+            Packet::from_str("[[6]]").unwrap(),
+        ];
+        let packets =
+            PacketReader::new(BufReader::new(file)).chain(divider_packets.iter().cloned().map(Ok));
+        let mut product = 1;
+
+        for (i, packet) in ExternalSorter::new(run_size, fan_in)
+            .sort(packets)?
+            .enumerate()
+        {
+            if divider_packets.contains(&packet?) {
+                product *= i + 1;
+            }
+        }
+
+        Ok(product)
+    }
 }
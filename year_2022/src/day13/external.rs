@@ -0,0 +1,239 @@
+use crate::day13::packet::Packet;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{remove_file, File};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Parses packets one line at a time, skipping the empty lines between pairs
+pub(crate) struct PacketReader<R: BufRead> {
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> PacketReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PacketReader<R> {
+    type Item = Result<Packet, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.to_string())),
+            };
+
+            if !line.trim().is_empty() {
+                return Some(Packet::from_str(&line));
+            }
+        }
+    }
+}
+
+/// Sorted packets written one per line to a temporary file, removed when dropped
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    fn write(packets: impl Iterator<Item = Result<Packet, String>>) -> Result<Self, String> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "day13_run_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let run = Self { path };
+        let mut writer = BufWriter::new(File::create(&run.path).map_err(|e| e.to_string())?);
+
+        for packet in packets {
+            writeln!(writer, "{}", packet?).map_err(|e| e.to_string())?;
+        }
+
+        writer.flush().map_err(|e| e.to_string())?;
+        Ok(run)
+    }
+
+    fn reader(&self) -> Result<PacketReader<BufReader<File>>, String> {
+        Ok(PacketReader::new(BufReader::new(
+            File::open(&self.path).map_err(|e| e.to_string())?,
+        )))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
+
+/// K-way merge of sorted runs, holding only the head packet of each run in memory
+pub(crate) struct Merge {
+    readers: Vec<PacketReader<BufReader<File>>>,
+    heads: BinaryHeap<Reverse<(Packet, usize)>>,
+
+    // Kept so the files outlive the readers
+    _runs: Vec<Run>,
+}
+
+impl Merge {
+    fn new(runs: Vec<Run>) -> Result<Self, String> {
+        let mut readers = runs
+            .iter()
+            .map(|run| run.reader())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut heads = BinaryHeap::new();
+
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(packet) = reader.next() {
+                heads.push(Reverse((packet?, i)));
+            }
+        }
+
+        Ok(Self {
+            readers,
+            heads,
+            _runs: runs,
+        })
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<Packet, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((packet, i)) = self.heads.pop()?;
+
+        match self.readers[i].next() {
+            Some(Ok(next)) => self.heads.push(Reverse((next, i))),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {}
+        }
+
+        Some(Ok(packet))
+    }
+}
+
+/// Sorts streams of packets that do not fit in memory.
+///
+/// At most `run_size` packets are sorted in memory at once, and at most
+/// `fan_in` runs are merged at once, merging in several passes if needed.
+pub(crate) struct ExternalSorter {
+    run_size: usize,
+    fan_in: usize,
+}
+
+impl ExternalSorter {
+    pub(crate) fn new(run_size: usize, fan_in: usize) -> Self {
+        Self {
+            run_size: run_size.max(1),
+            fan_in: fan_in.max(2),
+        }
+    }
+
+    pub(crate) fn sort(
+        &self,
+        packets: impl Iterator<Item = Result<Packet, String>>,
+    ) -> Result<Merge, String> {
+        let mut runs = vec![];
+        let mut buffer = Vec::with_capacity(self.run_size);
+
+        for packet in packets {
+            buffer.push(packet?);
+
+            if buffer.len() == self.run_size {
+                buffer.sort();
+                runs.push(Run::write(buffer.drain(..).map(Ok))?);
+            }
+        }
+
+        if !buffer.is_empty() {
+            buffer.sort();
+            runs.push(Run::write(buffer.into_iter().map(Ok))?);
+        }
+
+        while runs.len() > self.fan_in {
+            let mut merged = vec![];
+            let mut remaining = runs.into_iter();
+
+            loop {
+                let group = remaining.by_ref().take(self.fan_in).collect::<Vec<_>>();
+
+                if group.is_empty() {
+                    break;
+                }
+
+                merged.push(Run::write(Merge::new(group)?)?);
+            }
+
+            runs = merged;
+        }
+
+        Merge::new(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day13::Day13;
+
+    #[test]
+    fn sort_in_several_passes() {
+        let input = "[3]\n[1,[2]]\n\n[[1]]\n[]\n[5,5]\n[[4]]\n4\n[0]\n[[2],3]\n";
+        let mut expected = PacketReader::new(input.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        expected.sort();
+
+        let sorted = ExternalSorter::new(2, 2)
+            .sort(PacketReader::new(input.as_bytes()))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        let result = ExternalSorter::new(2, 2).sort(PacketReader::new("[1]\n[1,\n".as_bytes()));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sort_puzzle_input() {
+        let open = || BufReader::new(File::open("src/day13/input").unwrap());
+        let mut expected = PacketReader::new(open())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        expected.sort();
+
+        let sorted = ExternalSorter::new(16, 4)
+            .sort(PacketReader::new(open()))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn part_two_streaming() {
+        assert_eq!(Day13::part_two_streaming("src/day13/test", 3, 2), Ok(140));
+        assert_eq!(
+            Day13::part_two_streaming("src/day13/input", 16, 4),
+            Ok(Day13::part_two())
+        );
+        assert!(Day13::part_two_streaming("src/day13/missing", 3, 2).is_err());
+    }
+}
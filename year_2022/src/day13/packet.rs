@@ -1,65 +1,308 @@
-use serde_json::Value;
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) struct Packet(Value);
+pub(crate) enum Packet {
+    Int(i64),
+    List(Vec<Packet>),
+}
+
+/// Reads a packet straight from the bytes of the input, without intermediate strings
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            bytes: s.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        while self.position < self.bytes.len() && self.bytes[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+
+        self.bytes.get(self.position).copied()
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.bytes.get(self.position) {
+            Some(byte) => format!(
+                "Expected {} at position {}, got `{}`",
+                expected, self.position, *byte as char
+            ),
+            None => format!(
+                "Expected {} at position {}, got the end",
+                expected, self.position
+            ),
+        }
+    }
+
+    fn packet(&mut self) -> Result<Packet, String> {
+        match self.peek() {
+            Some(b'[') => self.list(),
+            Some(b'-' | b'0'..=b'9') => self.int(),
+            _ => Err(self.error("a list or a number")),
+        }
+    }
+
+    fn int(&mut self) -> Result<Packet, String> {
+        let negative = self.bytes[self.position] == b'-';
+        let mut value: i64 = 0;
+
+        if negative {
+            self.position += 1;
+        }
+
+        let start = self.position;
+
+        while let Some(digit @ b'0'..=b'9') = self.bytes.get(self.position).copied() {
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add((digit - b'0') as i64))
+                .ok_or_else(|| format!("Number at position {} is too large", start))?;
+            self.position += 1;
+        }
+
+        if self.position == start {
+            return Err(self.error("a digit"));
+        }
+
+        Ok(Packet::Int(if negative { -value } else { value }))
+    }
+
+    fn list(&mut self) -> Result<Packet, String> {
+        let mut packets = vec![];
+
+        // Skip `[`
+        self.position += 1;
+
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Packet::List(packets));
+        }
+
+        loop {
+            packets.push(self.packet()?);
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Packet::List(packets));
+                }
+                _ => return Err(self.error("`,` or `]`")),
+            }
+        }
+    }
+}
 
 impl FromStr for Packet {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let content = serde_json::from_str::<Value>(s);
+        let mut parser = Parser::new(s);
+        let packet = parser.packet()?;
+
+        match parser.peek() {
+            None => Ok(packet),
+            Some(_) => Err(parser.error("the end")),
+        }
+    }
+}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Int(value) => write!(f, "{}", value),
+            Packet::List(packets) => {
+                write!(f, "[")?;
+
+                for (i, packet) in packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", packet)?;
+                }
 
-        match content {
-            Ok(v) => Ok(Self(v)),
-            Err(e) => Err(e.to_string()),
+                write!(f, "]")
+            }
         }
     }
 }
 
 impl PartialOrd for Packet {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Packet(Value::Number(n1)), Packet(Value::Number(n2))) => {
-                let v1 = n1.as_i64().unwrap();
-                let v2 = n2.as_i64().unwrap();
+            (Packet::Int(v1), Packet::Int(v2)) => v1.cmp(v2),
+            (Packet::List(l1), Packet::List(l2)) => l1.iter().cmp(l2.iter()),
 
-                v1.partial_cmp(&v2)
-            }
-            (Packet(Value::Number(v1)), Packet(Value::Array(_))) => {
-                Packet(Value::Array(vec![Value::Number(v1.clone())])).partial_cmp(other)
+            // A number is compared as a list holding only that number
+            (Packet::Int(_), Packet::List(l2)) => std::slice::from_ref(self).iter().cmp(l2.iter()),
+            (Packet::List(l1), Packet::Int(_)) => l1.iter().cmp(std::slice::from_ref(other).iter()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Side {
+    Left,
+    Right,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Left => write!(f, "left"),
+            Side::Right => write!(f, "right"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Reason {
+    SmallerInteger(Side),
+    ListRanOut(Side),
+    Identical,
+}
+
+/// Why two packets compare the way they do.
+///
+/// `path` holds the list indices leading to the deciding element, or to the missing
+/// element when a list ran out. `promotions` holds where, along that path, an integer
+/// had to be turned into a list to be compared with one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Explanation {
+    pub(crate) ordering: Ordering,
+    pub(crate) path: Vec<usize>,
+    pub(crate) reason: Reason,
+    pub(crate) promotions: Vec<(Vec<usize>, Side)>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (path, side) in self.promotions.iter() {
+            writeln!(f, "{} integer at {:?} promoted to a list", side, path)?;
+        }
+
+        match self.reason {
+            Reason::SmallerInteger(side) => {
+                write!(f, "{} integer is smaller at {:?}", side, self.path)?
             }
-            (Packet(Value::Array(_)), Packet(Value::Number(_))) => {
-                other.partial_cmp(self).map(Ordering::reverse)
+            Reason::ListRanOut(side) => {
+                write!(f, "{} list ran out of items at {:?}", side, self.path)?
             }
-            (Packet(Value::Array(a1)), Packet(Value::Array(a2))) => {
-                for (v1, v2) in a1.iter().zip(a2.iter()) {
-                    if let Some(order) = Packet(v1.clone()).partial_cmp(&Packet(v2.clone())) {
-                        if matches!(order, Ordering::Less | Ordering::Greater) {
-                            return Some(order);
-                        }
-                    }
-                }
+            Reason::Identical => write!(f, "packets are identical")?,
+        }
 
-                if a2.len() < a1.len() {
-                    return Some(Ordering::Greater);
-                }
+        write!(f, ", ordering is {:?}", self.ordering)
+    }
+}
 
-                if a1.len() < a2.len() {
-                    return Some(Ordering::Less);
-                }
+impl Packet {
+    /// Same as `cmp`, but also tells which element decided the ordering and why
+    pub(crate) fn compare_explained(&self, other: &Self) -> Explanation {
+        let mut path = vec![];
+        let mut promotions = vec![];
+
+        match Self::explain(self, other, &mut path, &mut promotions) {
+            Some((ordering, reason)) => Explanation {
+                ordering,
+                path,
+                reason,
+                promotions,
+            },
+            None => Explanation {
+                ordering: Ordering::Equal,
+                path,
+                reason: Reason::Identical,
+                promotions,
+            },
+        }
+    }
 
-                return Some(Ordering::Equal);
+    fn explain(
+        left: &Packet,
+        right: &Packet,
+        path: &mut Vec<usize>,
+        promotions: &mut Vec<(Vec<usize>, Side)>,
+    ) -> Option<(Ordering, Reason)> {
+        let promoted = match (left, right) {
+            (Packet::Int(v1), Packet::Int(v2)) => {
+                return match v1.cmp(v2) {
+                    Ordering::Less => Some((Ordering::Less, Reason::SmallerInteger(Side::Left))),
+                    Ordering::Greater => {
+                        Some((Ordering::Greater, Reason::SmallerInteger(Side::Right)))
+                    }
+                    Ordering::Equal => None,
+                };
             }
-            _ => panic!("Branch {:?} {:?} not covered", self, other),
+            (Packet::List(l1), Packet::List(l2)) => {
+                return Self::explain_lists(l1, l2, path, promotions);
+            }
+            (Packet::Int(_), Packet::List(_)) => Side::Left,
+            (Packet::List(_), Packet::Int(_)) => Side::Right,
+        };
+
+        promotions.push((path.clone(), promoted));
+
+        let result = match (left, right) {
+            (Packet::Int(_), Packet::List(l2)) => {
+                Self::explain_lists(std::slice::from_ref(left), l2, path, promotions)
+            }
+            (Packet::List(l1), _) => {
+                Self::explain_lists(l1, std::slice::from_ref(right), path, promotions)
+            }
+            _ => unreachable!(),
+        };
+
+        // Promotions that did not decide anything are not worth reporting
+        if result.is_none() {
+            promotions.pop();
         }
+
+        result
     }
-}
 
-impl Ord for Packet {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+    fn explain_lists(
+        l1: &[Packet],
+        l2: &[Packet],
+        path: &mut Vec<usize>,
+        promotions: &mut Vec<(Vec<usize>, Side)>,
+    ) -> Option<(Ordering, Reason)> {
+        for (i, (v1, v2)) in l1.iter().zip(l2.iter()).enumerate() {
+            path.push(i);
+
+            if let Some(result) = Self::explain(v1, v2, path, promotions) {
+                return Some(result);
+            }
+
+            path.pop();
+        }
+
+        match l1.len().cmp(&l2.len()) {
+            Ordering::Less => {
+                path.push(l1.len());
+                Some((Ordering::Less, Reason::ListRanOut(Side::Left)))
+            }
+            Ordering::Greater => {
+                path.push(l2.len());
+                Some((Ordering::Greater, Reason::ListRanOut(Side::Right)))
+            }
+            Ordering::Equal => None,
+        }
     }
 }
 
@@ -190,4 +433,58 @@ mod tests {
         let p2 = Packet::from_str("[[[6,1,[]]],[8,[[6,10,0,4],2,10,[0,3,10,4],9],7,7]]").unwrap();
         assert_eq!(p1.partial_cmp(&p2), Some(Ordering::Greater));
     }
+
+    #[test]
+    fn display_round_trip() {
+        let raw = "[[10,[[10,2],2],3,2],[5,7,[2,[9,4],0,[4,7],0]],[],[-1,[[]]]]";
+        assert_eq!(Packet::from_str(raw).unwrap().to_string(), raw);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Packet::from_str("[1,2").is_err());
+        assert!(Packet::from_str("[1,,2]").is_err());
+        assert!(Packet::from_str("[1]]").is_err());
+        assert!(Packet::from_str("\"a\"").is_err());
+        assert!(Packet::from_str("").is_err());
+    }
+
+    #[test]
+    fn explain_smaller_integer() {
+        let p1 = Packet::from_str("[1,[2,[3,[4,[5,6,7]]]],8,9]").unwrap();
+        let p2 = Packet::from_str("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap();
+        let explanation = p1.compare_explained(&p2);
+
+        assert_eq!(explanation.ordering, Ordering::Greater);
+        assert_eq!(explanation.path, vec![1, 1, 1, 1, 2]);
+        assert_eq!(explanation.reason, Reason::SmallerInteger(Side::Right));
+        assert!(explanation.promotions.is_empty());
+    }
+
+    #[test]
+    fn explain_promotion_and_list_ran_out() {
+        let p1 = Packet::from_str("[[1],[2,3,4]]").unwrap();
+        let p2 = Packet::from_str("[[1],2]").unwrap();
+        let explanation = p1.compare_explained(&p2);
+
+        assert_eq!(explanation.ordering, Ordering::Greater);
+        assert_eq!(explanation.path, vec![1, 1]);
+        assert_eq!(explanation.reason, Reason::ListRanOut(Side::Right));
+        assert_eq!(explanation.promotions, vec![(vec![1], Side::Right)]);
+        assert_eq!(
+            explanation.to_string(),
+            "right integer at [1] promoted to a list\nright list ran out of items at [1, 1], ordering is Greater"
+        );
+    }
+
+    #[test]
+    fn explain_identical() {
+        let p1 = Packet::from_str("[[1],2]").unwrap();
+        let p2 = Packet::from_str("[1,[2]]").unwrap();
+        let explanation = p1.compare_explained(&p2);
+
+        assert_eq!(explanation.ordering, Ordering::Equal);
+        assert_eq!(explanation.reason, Reason::Identical);
+        assert!(explanation.promotions.is_empty());
+    }
 }
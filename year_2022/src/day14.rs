@@ -1,6 +1,10 @@
-use crate::day14::cave::{Cave, Outcome};
+use crate::day14::cave::Cave;
+use crate::day14::position::Position;
+
+pub use crate::day14::grain::{Event, Grain};
 
 mod cave;
+mod grain;
 mod io;
 mod position;
 
@@ -8,36 +12,36 @@ pub struct Day14;
 
 impl Day14 {
     pub fn part_one() -> usize {
-        let mut round = 0;
-        let mut cave = Cave::parse("src/day14/input");
-
-        loop {
-            if cave.pour_sand().is_err() {
-                break;
-            }
-
-            round += 1;
-        }
-
-        round
+        Self::resting_sand("src/day14/input", &[(500, 0)], false)
     }
 
     pub fn part_two() -> usize {
-        let mut round = 0;
-        let mut cave = Cave::parse("src/day14/input");
-
-        // Add the 2 extra layers
-        cave.push_air();
-        cave.push_floor();
+        Self::resting_sand("src/day14/input", &[(500, 0)], true)
+    }
 
-        loop {
-            if let Err(Outcome::CellBusy) = cave.pour_sand2() {
-                break;
-            }
+    /// Counts the grains that come to rest when pouring sand from `sources` in turn,
+    /// until one falls into the abyss or, with a `floor`, until every source is blocked
+    pub fn resting_sand(path: &str, sources: &[(i16, i16)], floor: bool) -> usize {
+        Self::pour(path, sources, floor)
+            .take_while(|grain| grain.event != Event::FellOut)
+            .count()
+    }
 
-            round += 1;
+    /// Pours sand from `sources` in turn and reports what happens to each grain.
+    ///
+    /// Without a `floor` grains fall out forever once the cave is full, so callers
+    /// have to decide when to stop.
+    pub fn pour(path: &str, sources: &[(i16, i16)], floor: bool) -> impl Iterator<Item = Grain> {
+        let sources = sources
+            .iter()
+            .map(|(x, y)| Position(*x, *y))
+            .collect::<Vec<_>>();
+        let cave = Cave::parse(path, &sources);
+
+        if floor {
+            cave.with_floor().grains()
+        } else {
+            cave.grains()
         }
-
-        round
     }
 }
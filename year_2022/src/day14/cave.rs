@@ -1,9 +1,11 @@
+use crate::day14::grain::{Event, Grain};
 use crate::day14::io::read_lines;
 use crate::day14::position::{Direction, Position};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use strum::IntoEnumIterator;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum Element {
     Nothing,
     Rock,
@@ -11,7 +13,7 @@ enum Element {
     RoofCrack,
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 pub(crate) enum Outcome {
     OutsideTheGrid,
     CellEmpty,
@@ -29,218 +31,329 @@ impl From<Element> for char {
     }
 }
 
+/// Only rock and sand are stored, so the cave can grow in any direction for free.
+///
+/// Without a floor, sand falling below the lowest rock leaves the cave.
+/// With a floor, the floor is two levels below the lowest rock and infinitely wide.
 pub(crate) struct Cave {
-    grid: Vec<Vec<Element>>,
-    sand_source: Position,
+    cells: HashMap<Position, Element>,
+    sources: Vec<Position>,
+    next_source: usize,
+
+    // Fall path of the last grain of each source, from the source down to the
+    // last free cell, and whether sand from other sources may have landed on it
+    paths: Vec<Vec<Position>>,
+    stale: Vec<bool>,
+    lowest_rock: i16,
+    floor: Option<i16>,
 }
 
 impl Cave {
     fn draw_line(&mut self, pos1: &Position, pos2: &Position) {
         // Same row
         if pos1.1 == pos2.1 {
-            let y = pos1.1 as usize;
             (pos1.0.min(pos2.0)..pos1.0.max(pos2.0) + 1).for_each(|x| {
-                self.grid[y][x as usize] = Element::Rock;
+                self.cells.insert(Position(x, pos1.1), Element::Rock);
             });
         } else if pos1.0 == pos2.0 {
-            let x = pos1.0 as usize;
             (pos1.1.min(pos2.1)..pos1.1.max(pos2.1) + 1).for_each(|y| {
-                self.grid[y as usize][x] = Element::Rock;
+                self.cells.insert(Position(pos1.0, y), Element::Rock);
             });
         }
     }
 
-    pub(crate) fn parse(path: &str) -> Self {
-        // Read original lines
-        let mut positions = read_lines(path);
-
-        // Shift left all positions
-        let min_x = Position::shift_left(&mut positions);
-
-        let max_y = positions
-            .iter()
-            .map(|positions| positions.iter().map(|position| position.1).max().unwrap())
-            .max()
-            .unwrap();
-        let max_x = positions
+    pub(crate) fn parse(path: &str, sources: &[Position]) -> Self {
+        let paths = read_lines(path);
+        let lowest_rock = paths
             .iter()
-            .map(|positions| positions.iter().map(|position| position.0).max().unwrap())
+            .flatten()
+            .map(|position| position.1)
             .max()
             .unwrap();
-        let mut grid = vec![vec![Element::Nothing; (max_x + 1) as usize]; (max_y + 1) as usize];
-
-        grid[0][(500 - min_x) as usize] = Element::RoofCrack;
 
         let mut cave = Self {
-            grid,
-            sand_source: Position(500 - min_x, 0),
+            cells: HashMap::new(),
+            sources: sources.to_vec(),
+            next_source: 0,
+            paths: vec![vec![]; sources.len()],
+            stale: vec![false; sources.len()],
+            lowest_rock,
+            floor: None,
         };
 
-        positions.into_iter().for_each(|positions| {
-            for i in 0..positions.len() - 1 {
-                cave.draw_line(&positions[i], &positions[i + 1]);
-            }
+        paths.into_iter().for_each(|positions| {
+            positions
+                .windows(2)
+                .for_each(|line| cave.draw_line(&line[0], &line[1]));
         });
 
         cave
     }
 
+    /// Adds the infinite floor two levels below the lowest rock
+    pub(crate) fn with_floor(mut self) -> Self {
+        self.floor = Some(self.lowest_rock + 2);
+        self
+    }
+
     /// Checks the outcome of trying to fill the position with sand
     fn outcome(&self, position: &Position) -> Outcome {
-        if position.0 < 0
-            || position.0 as usize >= self.grid[0].len()
-            || position.1 as usize >= self.grid.len()
-        {
-            return Outcome::OutsideTheGrid;
+        if self.floor == Some(position.1) || self.cells.contains_key(position) {
+            return Outcome::CellBusy;
         }
 
-        let element = &self.grid[position.1 as usize][position.0 as usize];
-
-        if *element == Element::Rock || *element == Element::Sand {
-            return Outcome::CellBusy;
+        if self.floor.is_none() && position.1 > self.lowest_rock {
+            return Outcome::OutsideTheGrid;
         }
 
         Outcome::CellEmpty
     }
 
-    fn push(&mut self, position: &Position, element: Element) {
-        self.grid[position.1 as usize][position.0 as usize] = element
-    }
+    /// Lets one grain fall from the source at `index` until it rests or leaves the cave.
+    ///
+    /// The grain resumes from the end of the previous grain's path instead of the
+    /// source: cells only ever fill up, so the path up to its last free cell is
+    /// still the one a new grain would follow.
+    fn sand_fall(&mut self, index: usize) -> Result<Position, Outcome> {
+        let mut path = std::mem::take(&mut self.paths[index]);
+
+        if self.stale[index] {
+            if let Some(busy) = path.iter().position(|p| self.cells.contains_key(p)) {
+                path.truncate(busy);
+            }
 
-    fn sand_fall(&mut self, current_position: Position) -> Result<Position, Outcome> {
-        match self.outcome(&current_position) {
-            // Outside the map -> we stop
-            Outcome::OutsideTheGrid => Err(Outcome::OutsideTheGrid),
+            self.stale[index] = false;
+        }
 
-            // Current cell is empty -> check if we can follow
-            Outcome::CellEmpty => {
-                for new_direction in Direction::iter() {
-                    let fall_result = self.sand_fall(current_position.translate(&new_direction));
+        if path.is_empty() {
+            if self.outcome(&self.sources[index]) == Outcome::CellBusy {
+                return Err(Outcome::CellBusy);
+            }
 
-                    if matches!(fall_result, Err(Outcome::OutsideTheGrid)) {
-                        return fall_result;
-                    }
+            path.push(self.sources[index].clone());
+        }
+
+        let result = 'fall: loop {
+            let current_position = path.last().unwrap().clone();
+
+            for direction in Direction::iter() {
+                let next = current_position.translate(&direction);
 
-                    if fall_result.is_ok() {
-                        return fall_result;
+                match self.outcome(&next) {
+                    Outcome::OutsideTheGrid => break 'fall Err(Outcome::OutsideTheGrid),
+                    Outcome::CellEmpty => {
+                        path.push(next);
+                        continue 'fall;
                     }
+                    Outcome::CellBusy => {}
                 }
+            }
 
-                self.push(&current_position, Element::Sand);
+            path.pop();
+            self.cells.insert(current_position.clone(), Element::Sand);
 
-                Ok(current_position)
-            }
+            // The grain may have landed on the path of another source
+            self.stale
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .for_each(|(_, stale)| *stale = true);
 
-            // Current cell is busy, we stop
-            Outcome::CellBusy => Err(Outcome::CellBusy),
-        }
-    }
+            break Ok(current_position);
+        };
+
+        self.paths[index] = path;
 
-    fn border_left(&mut self) {
-        let limit = self.grid.len() - 1;
+        result
+    }
 
-        // Add one column to the left, moves the sand source to the right
-        self.sand_source.0 += 1;
+    /// Pours one grain from the sources in turn, skipping the blocked ones.
+    ///
+    /// Fails with `CellBusy` once every source is blocked, or with `OutsideTheGrid`
+    /// when the grain falls out of the cave.
+    pub(crate) fn pour_sand(&mut self) -> Result<Position, Outcome> {
+        for _ in 0..self.sources.len() {
+            let index = self.next_source;
+            self.next_source = (self.next_source + 1) % self.sources.len();
 
-        for (idx, row) in self.grid.iter_mut().enumerate() {
-            if idx == limit {
-                row.insert(0, Element::Rock);
-            } else {
-                row.insert(0, Element::Nothing)
+            match self.sand_fall(index) {
+                Err(Outcome::CellBusy) => continue,
+                result => return result,
             }
         }
-    }
 
-    fn border_right(&mut self) {
-        let limit = self.grid.len() - 1;
+        Err(Outcome::CellBusy)
+    }
 
-        for (idx, row) in self.grid.iter_mut().enumerate() {
-            if idx == limit {
-                row.push(Element::Rock);
-            } else {
-                row.push(Element::Nothing);
-            }
+    /// Pours sand grain after grain, see `Grains`
+    pub(crate) fn grains(self) -> Grains {
+        Grains {
+            cave: self,
+            number: 0,
         }
     }
+}
 
-    fn sand_fall2(&mut self, current_position: Position) -> Result<Position, Outcome> {
-        if current_position.0 < 0 {
-            assert_eq!(current_position.0, -1);
-            self.border_left();
+/// Reports what happens to each grain poured in the cave.
+///
+/// Grains keep falling out of the cave forever once there is no floor under a
+/// source, so the iteration only ends when every source is blocked.
+pub(crate) struct Grains {
+    cave: Cave,
+    number: usize,
+}
 
-            // Try again, but now -1 becomes 0
-            return self.sand_fall2(Position(current_position.0 + 1, current_position.1));
-        }
+impl Iterator for Grains {
+    type Item = Grain;
 
-        if current_position.0 as usize >= self.grid[0].len() {
-            self.border_right();
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = match self.cave.pour_sand() {
+            Ok(position) => Event::Rest(position.0, position.1),
+            Err(Outcome::OutsideTheGrid) => Event::FellOut,
+            Err(_) => return None,
+        };
 
-            // Try again
-            return self.sand_fall2(Position(current_position.0, current_position.1));
-        }
+        self.number += 1;
 
-        let cave_element = &self.grid[current_position.1 as usize][current_position.0 as usize];
+        Some(Grain {
+            number: self.number,
+            event,
+        })
+    }
+}
 
-        if current_position.1 as usize == self.grid.len() - 2 {
-            return if matches!(cave_element, Element::Sand | Element::Rock) {
-                Err(Outcome::CellBusy)
-            } else {
-                self.grid[current_position.1 as usize][current_position.0 as usize] = Element::Sand;
-                Ok(current_position.clone())
-            };
-        }
+impl Display for Cave {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let positions = || self.cells.keys().chain(self.sources.iter());
+        let min_x = positions().map(|position| position.0).min().unwrap_or(0);
+        let max_x = positions().map(|position| position.0).max().unwrap_or(0);
+        let min_y = positions().map(|position| position.1).min().unwrap_or(0);
+        let max_y = self.floor.unwrap_or(self.lowest_rock);
+
+        let grid_string = (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| {
+                        let position = Position(x, y);
+
+                        if self.floor == Some(y) {
+                            char::from(Element::Rock)
+                        } else if let Some(element) = self.cells.get(&position) {
+                            char::from(*element)
+                        } else if self.sources.contains(&position) {
+                            char::from(Element::RoofCrack)
+                        } else {
+                            char::from(Element::Nothing)
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        write!(f, "{}", grid_string)
+    }
+}
 
-        if matches!(cave_element, Element::Sand | Element::Rock) {
-            return Err(Outcome::CellBusy);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for new_direction in Direction::iter() {
-            let fall_result = self.sand_fall2(current_position.translate(&new_direction));
+    fn count(mut cave: Cave) -> usize {
+        let mut round = 0;
 
-            if fall_result.is_ok() {
-                return fall_result;
-            }
+        while cave.pour_sand().is_ok() {
+            round += 1;
         }
 
-        // Nothing was inserted on lower levels and here it's space -> insert sand here
-        self.grid[current_position.1 as usize][current_position.0 as usize] = Element::Sand;
-
-        Ok(current_position.clone())
+        round
     }
 
-    pub(crate) fn pour_sand(&mut self) -> Result<Position, Outcome> {
-        self.sand_fall(self.sand_source.clone())
-    }
+    #[test]
+    fn example() {
+        let sources = [Position(500, 0)];
 
-    pub(crate) fn pour_sand2(&mut self) -> Result<Position, Outcome> {
-        self.sand_fall2(self.sand_source.clone())
+        assert_eq!(count(Cave::parse("src/day14/test", &sources)), 24);
+        assert_eq!(
+            count(Cave::parse("src/day14/test", &sources).with_floor()),
+            93
+        );
     }
 
-    fn push_level(&mut self, floor: Vec<Element>) {
-        self.grid.push(floor)
+    #[test]
+    fn several_sources() {
+        let sources = [Position(500, 0), Position(500, 0)];
+
+        // A duplicate source changes nothing, each grain still rests somewhere new
+        assert_eq!(
+            count(Cave::parse("src/day14/test", &sources).with_floor()),
+            93
+        );
+
+        let mut cave = Cave::parse("src/day14/test", &[Position(480, 0), Position(520, 0)]);
+        cave.floor = Some(3);
+
+        // Two disjoint piles of 9 grains on a floor 3 levels down
+        assert_eq!(count(cave), 18);
     }
 
-    pub(crate) fn push_air(&mut self) {
-        self.push_level(vec![Element::Nothing; self.grid[0].len()])
+    #[test]
+    fn resume_after_another_source() {
+        // The second source drops its grains right on the path of the first one
+        let sources = [Position(500, 0), Position(500, 5)];
+        let mut cave = Cave::parse("src/day14/test", &sources).with_floor();
+        let mut reference = Cave::parse("src/day14/test", &sources).with_floor();
+
+        loop {
+            // Without any memory of earlier paths, every grain starts from its source
+            reference.paths = vec![vec![]; sources.len()];
+
+            let result = cave.pour_sand();
+            assert_eq!(result, reference.pour_sand());
+
+            if result.is_err() {
+                break;
+            }
+        }
     }
 
-    pub(crate) fn push_floor(&mut self) {
-        self.push_level(vec![Element::Rock; self.grid[0].len()])
+    #[test]
+    fn grain_events() {
+        let grains = Cave::parse("src/day14/test", &[Position(500, 0)])
+            .grains()
+            .take(26)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            grains[0],
+            Grain {
+                number: 1,
+                event: Event::Rest(500, 8)
+            }
+        );
+        assert_eq!(grains[23].event, Event::Rest(495, 8));
+        assert_eq!(grains[24].event, Event::FellOut);
+        assert_eq!(grains[25].number, 26);
     }
-}
 
-impl Display for Cave {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let grid_string = self
-            .grid
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|element| char::from(element.clone()))
-                    .collect::<String>()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-        write!(f, "{}", grid_string)
+    #[test]
+    fn display() {
+        let mut cave = Cave::parse("src/day14/test", &[Position(500, 0)]);
+
+        while cave.pour_sand().is_ok() {}
+
+        assert_eq!(
+            cave.to_string(),
+            "\
+......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########."
+        );
     }
 }
@@ -0,0 +1,14 @@
+/// What happened to a grain of sand poured in the cave
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Event {
+    /// The grain came to rest at `(x, y)`
+    Rest(i16, i16),
+    FellOut,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Grain {
+    /// 1 for the first grain poured, across all sources
+    pub number: usize,
+    pub event: Event,
+}
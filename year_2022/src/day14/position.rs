@@ -11,7 +11,7 @@ pub(crate) enum Direction {
     DownRight,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub(crate) struct Position(pub(crate) i16, pub(crate) i16);
 
 impl FromStr for Position {
@@ -32,26 +32,6 @@ impl FromStr for Position {
 }
 
 impl Position {
-    /// Takes a list of positions and shifts to the left as much as possible
-    ///
-    /// # Examples
-    ///
-    /// [Position(5, 6), Position(6, 6)] -> [Position(0, 6), Position(1, 6)]
-    pub(crate) fn shift_left(positions: &mut Vec<Vec<Self>>) -> i16 {
-        let min_x = positions
-            .iter()
-            .map(|positions| positions.iter().map(|position| position.0).min().unwrap())
-            .min()
-            .unwrap();
-        positions.iter_mut().for_each(|positions| {
-            positions
-                .iter_mut()
-                .for_each(|position| position.0 -= min_x);
-        });
-
-        min_x
-    }
-
     pub(crate) fn translate(&self, direction: &Direction) -> Self {
         match direction {
             Direction::Down => Self(self.0, self.1 + 1),
@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub(crate) struct Position(pub(crate) i32, pub(crate) i32);
+
+impl Position {
+    pub(crate) fn manhattan_distance(&self, other: &Self) -> usize {
+        ((self.0 - other.0).abs() + (self.1 - other.1).abs()) as usize
+    }
+
+    /// Coordinates rotated by 45 degrees, `(x + y, x - y)`, in which the
+    /// diamond covered by a sensor becomes an axis aligned square
+    pub(crate) fn rotated(&self) -> (i64, i64) {
+        (self.0 as i64 + self.1 as i64, self.0 as i64 - self.1 as i64)
+    }
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    /// Parses the `x=2, y=18` part of a report line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s
+            .split_once(", ")
+            .ok_or_else(|| format!("Cannot parse position {}", s))?;
+        let coordinate = |token: &str, prefix: &str| {
+            token
+                .strip_prefix(prefix)
+                .and_then(|value| value.parse::<i32>().ok())
+                .ok_or_else(|| format!("Cannot parse position {}", s))
+        };
+
+        Ok(Self(coordinate(x, "x=")?, coordinate(y, "y=")?))
+    }
+}
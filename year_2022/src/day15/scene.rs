@@ -0,0 +1,283 @@
+use crate::day15::position::Position;
+use crate::interval_set::IntervalSet;
+use std::fs::read_to_string;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+struct Sensor {
+    position: Position,
+    beacon: Position,
+    radius: usize,
+}
+
+impl FromStr for Sensor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sensor, beacon) = s
+            .strip_prefix("Sensor at ")
+            .and_then(|s| s.split_once(": closest beacon is at "))
+            .ok_or_else(|| format!("Cannot parse report {}", s))?;
+        let position = Position::from_str(sensor)?;
+        let beacon = Position::from_str(beacon)?;
+
+        Ok(Self {
+            radius: position.manhattan_distance(&beacon),
+            position,
+            beacon,
+        })
+    }
+}
+
+impl Sensor {
+    fn covers(&self, position: &Position) -> bool {
+        self.position.manhattan_distance(position) <= self.radius
+    }
+
+    /// Columns covered on `row`.
+    ///
+    /// On the row of the sensor the signal looks like `# * radius` S `# * radius`,
+    /// and it shrinks by 2 with every row further away.
+    fn signal(&self, row: i32) -> Option<RangeInclusive<i32>> {
+        let distance_between_rows = (self.position.1 - row).unsigned_abs() as usize;
+
+        (distance_between_rows <= self.radius).then(|| {
+            let half_width = (self.radius - distance_between_rows) as i32;
+            self.position.0 - half_width..=self.position.0 + half_width
+        })
+    }
+
+    /// Covered square in rotated coordinates, as half open `u` and `v` ranges
+    fn square(&self) -> ((i64, i64), (i64, i64)) {
+        let (u, v) = self.position.rotated();
+        let radius = self.radius as i64;
+
+        ((u - radius, u + radius + 1), (v - radius, v + radius + 1))
+    }
+}
+
+/// Sensors with the area they cover: every position at most as far from the sensor
+/// as its closest beacon.
+#[derive(Debug)]
+pub struct Scene {
+    sensors: Vec<Sensor>,
+}
+
+impl FromStr for Scene {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            sensors: s
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(Sensor::from_str)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Scene {
+    pub fn parse(path: &str) -> Self {
+        Self::from_str(&read_to_string(path).unwrap()).unwrap()
+    }
+
+    pub fn is_covered(&self, (x, y): (i32, i32)) -> bool {
+        let position = Position(x, y);
+        self.sensors.iter().any(|sensor| sensor.covers(&position))
+    }
+
+    /// Columns covered on `row`
+    pub fn signals(&self, row: i32) -> IntervalSet<i32> {
+        self.sensors
+            .iter()
+            .filter_map(|sensor| sensor.signal(row))
+            .collect()
+    }
+
+    /// Number of positions in `columns`, which cannot exceed 2^32
+    fn length(columns: IntervalSet<i32>) -> u64 {
+        columns
+            .total_length()
+            .and_then(|length| u64::try_from(length).ok())
+            .expect("A row has at most 2^32 columns")
+    }
+
+    /// Number of covered positions on `row`
+    pub fn covered(&self, row: i32) -> u64 {
+        Self::length(self.signals(row))
+    }
+
+    /// Number of covered positions on each of `rows`
+    pub fn coverage(&self, rows: RangeInclusive<i32>) -> Vec<u64> {
+        rows.map(|row| self.covered(row)).collect()
+    }
+
+    /// Number of covered positions on `row` that cannot hold a beacon
+    pub fn beaconless(&self, row: i32) -> u64 {
+        let beacons = self
+            .sensors
+            .iter()
+            .filter(|sensor| sensor.beacon.1 == row)
+            .map(|sensor| sensor.beacon.0..=sensor.beacon.0)
+            .collect();
+
+        Self::length(self.signals(row).difference(&beacons))
+    }
+
+    /// Sorted lines splitting `bounds` along the edges of the squares
+    fn cell_lines(bounds: (i64, i64), edges: impl Iterator<Item = (i64, i64)>) -> Vec<i64> {
+        let mut lines = edges
+            .flat_map(|(start, end)| [start, end])
+            .map(|line| line.clamp(bounds.0, bounds.1))
+            .chain([bounds.0, bounds.1])
+            .collect::<Vec<_>>();
+
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// Index of `line` among sorted `lines`, once clamped to the first and last of them
+    fn line_index(lines: &[i64], line: i64) -> usize {
+        lines
+            .binary_search(&line.clamp(lines[0], lines[lines.len() - 1]))
+            .unwrap()
+    }
+
+    /// All positions of the box between the `min` and `max` corners no sensor covers,
+    /// sorted by row then column.
+    ///
+    /// In rotated coordinates (`u = x + y`, `v = x - y`) every sensor covers a square,
+    /// so the boundary lines of the squares and of the box split the plane into a
+    /// quadratic number of cells, each either fully covered or not. Cells are swept
+    /// along `u`, keeping how many squares cover each `v` cell, so finding the
+    /// uncovered cells takes quadratic time in the number of sensors.
+    ///
+    /// Every uncovered position is returned, so the result is as large as the
+    /// uncovered area: up to the whole box, e.g. when there are no sensors. Only
+    /// use it when few positions are expected to be left, as in the puzzle.
+    pub fn uncovered(&self, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        let (min_x, min_y, max_x, max_y) = (min.0 as i64, min.1 as i64, max.0 as i64, max.1 as i64);
+        let box_u = (min_x + min_y, max_x + max_y + 1);
+        let box_v = (min_x - max_y, max_x - min_y + 1);
+        let squares = self
+            .sensors
+            .iter()
+            .map(|sensor| sensor.square())
+            .collect::<Vec<_>>();
+
+        let u_lines = Self::cell_lines(box_u, squares.iter().map(|square| square.0));
+        let v_lines = Self::cell_lines(box_v, squares.iter().map(|square| square.1));
+
+        // Squares entering and leaving the sweep at each `u` line, by their `v` cells
+        let mut events = vec![vec![]; u_lines.len()];
+
+        for (square_u, square_v) in &squares {
+            let v_cells = (
+                Self::line_index(&v_lines, square_v.0),
+                Self::line_index(&v_lines, square_v.1),
+            );
+
+            events[Self::line_index(&u_lines, square_u.0)].push((v_cells, 1));
+            events[Self::line_index(&u_lines, square_u.1)].push((v_cells, -1));
+        }
+
+        // Number of squares covering each `v` cell of the current `u` cell
+        let mut coverage = vec![0; v_lines.len() - 1];
+        let mut positions = vec![];
+
+        for (u_index, u_cell) in u_lines.windows(2).enumerate() {
+            for &((start, end), change) in &events[u_index] {
+                coverage[start..end]
+                    .iter_mut()
+                    .for_each(|count| *count += change);
+            }
+
+            for (v_cell, _) in v_lines
+                .windows(2)
+                .zip(&coverage)
+                .filter(|(_, count)| **count == 0)
+            {
+                // Rows of the cell crossing the box, which is a diamond in rotated coordinates
+                let first_u = u_cell[0]
+                    .max(2 * min_x - v_cell[1] + 1)
+                    .max(v_cell[0] + 2 * min_y);
+                let last_u = (u_cell[1] - 1)
+                    .min(2 * max_y + v_cell[1] - 1)
+                    .min(2 * max_x - v_cell[0]);
+
+                for u in first_u..=last_u {
+                    // Keep `v` inside the cell and `(x, y)` inside the box
+                    let start = v_cell[0].max(2 * min_x - u).max(u - 2 * max_y);
+                    let end = (v_cell[1] - 1).min(2 * max_x - u).min(u - 2 * min_y);
+
+                    // `u` and `v` of integer positions have the same parity
+                    let start = start + (start - u).rem_euclid(2);
+
+                    positions.extend(
+                        (start..=end)
+                            .step_by(2)
+                            .map(|v| (((u + v) / 2) as i32, ((u - v) / 2) as i32)),
+                    );
+                }
+            }
+        }
+
+        positions.sort_unstable_by_key(|&(x, y)| (y, x));
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let scene = Scene::parse("src/day15/test");
+
+        assert_eq!(scene.beaconless(10), 26);
+        assert_eq!(scene.uncovered((0, 0), (20, 20)), vec![(14, 11)]);
+        assert!(!scene.is_covered((14, 11)));
+        assert!(scene.is_covered((13, 11)));
+    }
+
+    #[test]
+    fn uncovered_matches_point_queries() {
+        let scene = Scene::parse("src/day15/test");
+        let (min, max) = ((-12, -7), (35, 30));
+
+        let expected = (min.1..=max.1)
+            .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+            .filter(|&position| !scene.is_covered(position))
+            .collect::<Vec<_>>();
+
+        assert_eq!(scene.uncovered(min, max), expected);
+        assert_eq!(
+            scene.coverage(min.1..=max.1),
+            (min.1..=max.1)
+                .map(|y| (-20..=50).filter(|&x| scene.is_covered((x, y))).count() as u64)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn uncovered_away_from_sensors() {
+        let scene = Scene::parse("src/day15/test");
+
+        for (min, max) in [((100, 100), (103, 102)), ((-3, 25), (4, 40))] {
+            let expected = (min.1..=max.1)
+                .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+                .filter(|&position| !scene.is_covered(position))
+                .collect::<Vec<_>>();
+
+            assert_eq!(scene.uncovered(min, max), expected);
+        }
+
+        // Without sensors, every position of the box is listed
+        let scene = Scene::from_str("").unwrap();
+        assert_eq!(scene.uncovered((0, 0), (2, 1)).len(), 6);
+    }
+}
@@ -0,0 +1,73 @@
+use num_traits::PrimInt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// Integer type usable for the coordinates of a droplet
+pub trait Scalar: PrimInt + Hash + FromStr {}
+
+impl<T: PrimInt + Hash + FromStr> Scalar for T {}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Coordinate<T: Scalar>(pub T, pub T, pub T);
+
+impl<T: Scalar> Coordinate<T> {
+    /// The cubes sharing a face with this one, skipping the ones `T` cannot represent
+    pub fn neighbours(&self) -> impl Iterator<Item = Self> {
+        let one = T::one();
+
+        [
+            (self.0.checked_sub(&one), Some(self.1), Some(self.2)),
+            (self.0.checked_add(&one), Some(self.1), Some(self.2)),
+            (Some(self.0), self.1.checked_sub(&one), Some(self.2)),
+            (Some(self.0), self.1.checked_add(&one), Some(self.2)),
+            (Some(self.0), Some(self.1), self.2.checked_sub(&one)),
+            (Some(self.0), Some(self.1), self.2.checked_add(&one)),
+        ]
+        .into_iter()
+        .filter_map(|(x, y, z)| Some(Self(x?, y?, z?)))
+    }
+}
+
+impl<T: Scalar> Coordinate<T> {
+    /// Same cube with `i128` values, wide enough to step past the bounds of `T`.
+    ///
+    /// Panics for `u128` values above `i128::MAX`.
+    pub(crate) fn widened(&self) -> Coordinate<i128> {
+        let widen = |value: T| value.to_i128().expect("Coordinate does not fit in i128");
+
+        Coordinate(widen(self.0), widen(self.1), widen(self.2))
+    }
+}
+
+impl Coordinate<i128> {
+    /// Same cube with `T` values, if `T` can represent them
+    pub(crate) fn narrowed<T: Scalar>(&self) -> Option<Coordinate<T>> {
+        Some(Coordinate(
+            T::from(self.0)?,
+            T::from(self.1)?,
+            T::from(self.2)?,
+        ))
+    }
+}
+
+impl<T: Scalar> FromStr for Coordinate<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .trim()
+            .split(',')
+            .map(|token| token.trim().parse::<T>().ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("Cannot parse coordinate {}", s))?;
+
+        match values[..] {
+            [x, y, z] => Ok(Self(x, y, z)),
+            _ => Err(format!(
+                "Expected 3 values, found {} in {}",
+                values.len(),
+                s
+            )),
+        }
+    }
+}
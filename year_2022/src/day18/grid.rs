@@ -1,27 +1,49 @@
-use std::collections::HashSet;
+use crate::day18::coordinate::{Coordinate, Scalar};
+use crate::day18::mesh::{Mesh, Surface};
+use std::collections::{HashSet, VecDeque};
+use std::fs::read_to_string;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub(crate) struct Coordinate(pub(crate) i8, pub(crate) i8, pub(crate) i8);
-
-pub(crate) struct Grid {
-    pub(crate) cubes: HashSet<Coordinate>,
-    pub(crate) bot_left: Coordinate,
-    pub(crate) top_right: Coordinate,
+/// Lava droplet made of unit cubes, with the bounding box of the cubes
+#[derive(Debug, Clone)]
+pub struct Grid<T: Scalar> {
+    cubes: HashSet<Coordinate<T>>,
+    bot_left: Coordinate<T>,
+    top_right: Coordinate<T>,
 }
 
-impl Default for Grid {
+impl<T: Scalar> Default for Grid<T> {
     fn default() -> Self {
         Self {
             cubes: Default::default(),
 
-            bot_left: Coordinate(i8::MAX, i8::MAX, i8::MAX),
-            top_right: Coordinate(i8::MIN, i8::MIN, i8::MIN),
+            bot_left: Coordinate(T::max_value(), T::max_value(), T::max_value()),
+            top_right: Coordinate(T::min_value(), T::min_value(), T::min_value()),
         }
     }
 }
 
-impl Grid {
-    pub(crate) fn add(&mut self, point: Coordinate) {
+impl<T: Scalar> FromStr for Grid<T> {
+    type Err = String;
+
+    /// One `x,y,z` cube per line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid = Self::default();
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            grid.add(Coordinate::from_str(line)?);
+        }
+
+        Ok(grid)
+    }
+}
+
+impl<T: Scalar> Grid<T> {
+    pub fn parse(path: &str) -> Self {
+        Self::from_str(&read_to_string(path).unwrap()).unwrap()
+    }
+
+    pub fn add(&mut self, point: Coordinate<T>) {
         self.top_right.0 = self.top_right.0.max(point.0);
         self.top_right.1 = self.top_right.1.max(point.1);
         self.top_right.2 = self.top_right.2.max(point.2);
@@ -33,86 +55,204 @@ impl Grid {
         self.cubes.insert(point);
     }
 
-    pub(crate) fn surface(&self) -> usize {
-        self.cubes
+    pub fn cubes(&self) -> impl Iterator<Item = &Coordinate<T>> {
+        self.cubes.iter()
+    }
+
+    pub fn contains(&self, cube: &Coordinate<T>) -> bool {
+        self.cubes.contains(cube)
+    }
+
+    /// Triangle mesh of the exposed faces, ready for `Mesh::to_obj` or `Mesh::to_stl`
+    pub fn mesh(&self, surface: Surface) -> Mesh {
+        Mesh::new(self, surface)
+    }
+
+    /// Number of cube faces not touching another cube, air pockets included
+    pub fn surface(&self) -> usize {
+        // Neighbours `T` cannot represent are not cubes, so their faces are exposed too
+        let touching = self
+            .cubes
             .iter()
-            .map(|coordinate| {
-                [
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0 - 1, coordinate.1, coordinate.2))
-                        as usize,
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0 + 1, coordinate.1, coordinate.2))
-                        as usize,
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0, coordinate.1 - 1, coordinate.2))
-                        as usize,
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0, coordinate.1 + 1, coordinate.2))
-                        as usize,
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0, coordinate.1, coordinate.2 - 1))
-                        as usize,
-                    !self
-                        .cubes
-                        .contains(&Coordinate(coordinate.0, coordinate.1, coordinate.2 + 1))
-                        as usize,
-                ]
-                .iter()
-                .sum::<usize>()
-            })
-            .sum()
-    }
-
-    fn inside_limit(&self, position: &Coordinate) -> bool {
-        self.bot_left.0 <= position.0
-            && position.0 <= self.top_right.0
-            && self.bot_left.1 <= position.1
-            && position.1 <= self.top_right.1
-            && self.bot_left.2 <= position.2
-            && position.2 <= self.top_right.2
-    }
-
-    pub(crate) fn flood(&self, position: Coordinate, water_regions: &mut HashSet<Coordinate>) {
-        if water_regions.contains(&position)
-            || self.cubes.contains(&position)
-            || !self.inside_limit(&position)
+            .flat_map(|cube| cube.neighbours())
+            .filter(|neighbour| self.cubes.contains(neighbour))
+            .count();
+
+        6 * self.cubes.len() - touching
+    }
+
+    /// Number of cube faces water can reach from outside the droplet
+    pub fn exterior_surface(&self) -> usize {
+        let pocket_faces = self
+            .pockets()
+            .iter()
+            .flatten()
+            .flat_map(|air| air.neighbours())
+            .filter(|neighbour| self.cubes.contains(neighbour))
+            .count();
+
+        self.surface() - pocket_faces
+    }
+
+    /// Total number of air cubes trapped inside the droplet
+    pub fn enclosed_volume(&self) -> usize {
+        self.pockets().iter().map(|pocket| pocket.len()).sum()
+    }
+
+    /// Connected regions of air water cannot reach, in no particular order
+    pub fn pockets(&self) -> Vec<HashSet<Coordinate<T>>> {
+        let exterior = self.exterior();
+        let mut visited = HashSet::new();
+        let mut pockets = vec![];
+
+        for air in self
+            .cubes
+            .iter()
+            .flat_map(|cube| cube.widened().neighbours())
         {
-            return;
+            if self.is_cube(&air) || exterior.contains(&air) || visited.contains(&air) {
+                continue;
+            }
+
+            // Trapped air is surrounded by cubes, so the fill never leaves the droplet
+            // and `T` can represent all of it
+            let pocket = self.fill(air, |_| true);
+            visited.extend(pocket.iter().copied());
+            pockets.push(pocket.iter().filter_map(|air| air.narrowed()).collect());
         }
 
-        water_regions.insert(position.clone());
+        pockets
+    }
 
-        self.flood(
-            Coordinate(position.0 + 1, position.1, position.2),
-            water_regions,
-        );
-        self.flood(
-            Coordinate(position.0 - 1, position.1, position.2),
-            water_regions,
-        );
+    fn is_cube(&self, position: &Coordinate<i128>) -> bool {
+        position
+            .narrowed()
+            .is_some_and(|position| self.cubes.contains(&position))
+    }
 
-        self.flood(
-            Coordinate(position.0, position.1 - 1, position.2),
-            water_regions,
-        );
-        self.flood(
-            Coordinate(position.0, position.1 + 1, position.2),
-            water_regions,
-        );
+    /// Air reachable from outside, within the bounding box grown by one in every direction.
+    ///
+    /// The box is computed with `i128` values, so it still surrounds cubes at the bounds of `T`.
+    fn exterior(&self) -> HashSet<Coordinate<i128>> {
+        if self.cubes.is_empty() {
+            return HashSet::new();
+        }
 
-        self.flood(
-            Coordinate(position.0, position.1, position.2 - 1),
-            water_regions,
-        );
-        self.flood(
-            Coordinate(position.0, position.1, position.2 + 1),
-            water_regions,
+        let (bot_left, top_right) = (self.bot_left.widened(), self.top_right.widened());
+        let min = Coordinate(bot_left.0 - 1, bot_left.1 - 1, bot_left.2 - 1);
+        let max = Coordinate(top_right.0 + 1, top_right.1 + 1, top_right.2 + 1);
+
+        self.fill(max, |position| {
+            (min.0..=max.0).contains(&position.0)
+                && (min.1..=max.1).contains(&position.1)
+                && (min.2..=max.2).contains(&position.2)
+        })
+    }
+
+    /// Breadth first search over the air connected to `start`, staying where `inside` holds
+    fn fill<F>(&self, start: Coordinate<i128>, inside: F) -> HashSet<Coordinate<i128>>
+    where
+        F: Fn(&Coordinate<i128>) -> bool,
+    {
+        let mut region = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(position) = queue.pop_front() {
+            for neighbour in position.neighbours() {
+                if !self.is_cube(&neighbour) && inside(&neighbour) && region.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let grid = Grid::<i32>::parse("src/day18/test");
+
+        assert_eq!(grid.surface(), 64);
+        assert_eq!(grid.exterior_surface(), 58);
+        assert_eq!(grid.enclosed_volume(), 1);
+        assert_eq!(grid.pockets(), vec![HashSet::from([Coordinate(2, 2, 5)])]);
+    }
+
+    #[test]
+    fn hollow_cube_beyond_i8() {
+        // 5x5x5 shell far away from the origin, with a 3x3x3 hole split in two by a wall
+        let mut grid = Grid::<i64>::default();
+        let offset = 1_000_000_000_000;
+
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..5 {
+                    let shell = [x, y, z].iter().any(|c| *c == 0 || *c == 4);
+
+                    if shell || x == 2 {
+                        grid.add(Coordinate(x + offset, y - offset, z));
+                    }
+                }
+            }
+        }
+
+        let mut sizes = grid
+            .pockets()
+            .iter()
+            .map(|pocket| pocket.len())
+            .collect::<Vec<_>>();
+        sizes.sort();
+
+        assert_eq!(sizes, vec![9, 9]);
+        assert_eq!(grid.enclosed_volume(), 18);
+        assert_eq!(grid.exterior_surface(), 6 * 25);
+    }
+
+    #[test]
+    fn cubes_at_the_bounds_of_the_type() {
+        let mut grid = Grid::<i8>::default();
+        grid.add(Coordinate(i8::MAX, 0, 0));
+
+        assert_eq!(grid.surface(), 6);
+        assert_eq!(grid.exterior_surface(), 6);
+
+        // A hollow 3x3x3 cube in the corner of `i8`, the hole being at `i8::MAX - 1`
+        let mut grid = Grid::<i8>::default();
+
+        for x in i8::MAX - 2..=i8::MAX {
+            for y in i8::MIN..=i8::MIN + 2 {
+                for z in i8::MAX - 2..=i8::MAX {
+                    if (x, y, z) != (i8::MAX - 1, i8::MIN + 1, i8::MAX - 1) {
+                        grid.add(Coordinate(x, y, z));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(grid.surface(), 6 * 9 + 6);
+        assert_eq!(grid.exterior_surface(), 6 * 9);
+        assert_eq!(
+            grid.pockets(),
+            vec![HashSet::from([Coordinate(
+                i8::MAX - 1,
+                i8::MIN + 1,
+                i8::MAX - 1
+            )])]
         );
     }
+
+    #[test]
+    fn deep_flood_does_not_overflow_the_stack() {
+        // A long thin wall, so the exterior holds a few hundred thousand air cubes
+        let mut grid = Grid::<i16>::default();
+        (0..5_000).for_each(|x| grid.add(Coordinate(x, 0, 0)));
+
+        assert_eq!(grid.exterior_surface(), grid.surface());
+        assert_eq!(grid.surface(), 4 * 5_000 + 2);
+    }
 }
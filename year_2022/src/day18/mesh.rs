@@ -0,0 +1,222 @@
+use crate::day18::coordinate::{Coordinate, Scalar};
+use crate::day18::grid::Grid;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Which cube faces end up in a mesh
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Surface {
+    /// Every face not touching another cube, including the walls of air pockets
+    All,
+    /// Only the faces water can reach from outside the droplet
+    Exterior,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Triangle {
+    /// Indices in `Mesh::vertices`, counter clockwise when seen from outside
+    pub vertices: [usize; 3],
+    pub normal: [i8; 3],
+}
+
+/// Triangle mesh of the faces of a droplet, two triangles per face.
+///
+/// Vertices are the corners of the unit cubes, a cube at `(x, y, z)` spanning
+/// `(x, y, z)` to `(x + 1, y + 1, z + 1)`, and are shared between triangles.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Coordinate<i128>>,
+    pub triangles: Vec<Triangle>,
+}
+
+impl Mesh {
+    pub(crate) fn new<T: Scalar>(grid: &Grid<T>, surface: Surface) -> Self {
+        let pockets = match surface {
+            Surface::All => HashSet::new(),
+            Surface::Exterior => grid.pockets().into_iter().flatten().collect(),
+        };
+        let mut mesh = Self::default();
+        let mut indices = HashMap::new();
+
+        for cube in grid.cubes() {
+            let wide = cube.widened();
+            let origin = [wide.0, wide.1, wide.2];
+
+            for axis in 0..3 {
+                for sign in [-1, 1] {
+                    let mut neighbour = origin;
+                    neighbour[axis] += sign as i128;
+
+                    // Neighbours `T` cannot represent are neither cubes nor pockets, like in
+                    // `Grid::surface`
+                    let neighbour = Coordinate(neighbour[0], neighbour[1], neighbour[2]).narrowed();
+                    let exposed = neighbour.is_none_or(|neighbour| {
+                        !grid.contains(&neighbour) && !pockets.contains(&neighbour)
+                    });
+
+                    if exposed {
+                        mesh.push_face(&mut indices, origin, axis, sign);
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+
+    /// Adds the face of the cube at `origin` pointing towards `sign` along `axis`
+    fn push_face(
+        &mut self,
+        indices: &mut HashMap<Coordinate<i128>, usize>,
+        origin: [i128; 3],
+        axis: usize,
+        sign: i8,
+    ) {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+        // Going around (0, 0), (1, 0), (1, 1), (0, 1) in the (u, v) plane turns
+        // counter clockwise around the positive direction of `axis`
+        let mut corners = [(0, 0), (1, 0), (1, 1), (0, 1)];
+
+        if sign < 0 {
+            corners.reverse();
+        }
+
+        let corners = corners.map(|(du, dv)| {
+            let mut corner = origin;
+            corner[axis] += (sign > 0) as i128;
+            corner[u] += du;
+            corner[v] += dv;
+
+            let vertex = Coordinate(corner[0], corner[1], corner[2]);
+
+            *indices.entry(vertex).or_insert_with(|| {
+                self.vertices.push(vertex);
+                self.vertices.len() - 1
+            })
+        });
+
+        let mut normal = [0; 3];
+        normal[axis] = sign;
+
+        self.triangles.push(Triangle {
+            vertices: [corners[0], corners[1], corners[2]],
+            normal,
+        });
+        self.triangles.push(Triangle {
+            vertices: [corners[0], corners[2], corners[3]],
+            normal,
+        });
+    }
+
+    /// Number of cube faces in the mesh
+    pub fn faces(&self) -> usize {
+        self.triangles.len() / 2
+    }
+
+    /// Wavefront OBJ text, with 1 based vertex indices
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+
+        for vertex in &self.vertices {
+            writeln!(obj, "v {} {} {}", vertex.0, vertex.1, vertex.2).unwrap();
+        }
+
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.vertices;
+            writeln!(obj, "f {} {} {}", a + 1, b + 1, c + 1).unwrap();
+        }
+
+        obj
+    }
+
+    /// ASCII STL text of a solid called `name`
+    pub fn to_stl(&self, name: &str) -> String {
+        let mut stl = format!("solid {}\n", name);
+
+        for triangle in &self.triangles {
+            let [x, y, z] = triangle.normal;
+            writeln!(stl, "  facet normal {} {} {}", x, y, z).unwrap();
+            writeln!(stl, "    outer loop").unwrap();
+
+            for index in triangle.vertices {
+                let vertex = &self.vertices[index];
+                writeln!(stl, "      vertex {} {} {}", vertex.0, vertex.1, vertex.2).unwrap();
+            }
+
+            writeln!(stl, "    endloop").unwrap();
+            writeln!(stl, "  endfacet").unwrap();
+        }
+
+        writeln!(stl, "endsolid {}", name).unwrap();
+        stl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_counts_match_surfaces() {
+        let check = |grid: Grid<i8>| {
+            assert_eq!(grid.mesh(Surface::All).faces(), grid.surface());
+            assert_eq!(
+                grid.mesh(Surface::Exterior).faces(),
+                grid.exterior_surface()
+            );
+        };
+
+        check(Grid::parse("src/day18/test"));
+
+        // The same droplet pushed against the upper bounds of `i8`
+        let mut grid = Grid::default();
+        Grid::<i8>::parse("src/day18/test")
+            .cubes()
+            .for_each(|cube| grid.add(Coordinate(cube.0 + 121, cube.1 + 121, cube.2 + 121)));
+        check(grid);
+    }
+
+    #[test]
+    fn single_cube() {
+        let mut grid = Grid::<i8>::default();
+        grid.add(Coordinate(i8::MAX, 0, 0));
+        let mesh = grid.mesh(Surface::Exterior);
+
+        // The corners are shared by the 6 faces, even past `i8::MAX`
+        assert_eq!(mesh.faces(), 6);
+        assert_eq!(mesh.faces(), grid.surface());
+        assert_eq!(grid.mesh(Surface::All).faces(), grid.surface());
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.triangles.len(), 12);
+        assert!(mesh.vertices.contains(&Coordinate(128, 1, 1)));
+
+        // Every triangle turns counter clockwise around its normal
+        for triangle in &mesh.triangles {
+            let [a, b, c] = triangle.vertices.map(|index| mesh.vertices[index]);
+            let (ab, ac) = (
+                [b.0 - a.0, b.1 - a.1, b.2 - a.2],
+                [c.0 - a.0, c.1 - a.1, c.2 - a.2],
+            );
+            let cross = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+
+            assert_eq!(cross, triangle.normal.map(|n| n as i128));
+        }
+
+        let obj = mesh.to_obj();
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 8);
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("f ")).count(),
+            12
+        );
+
+        let stl = mesh.to_stl("cube");
+        assert!(stl.starts_with("solid cube\n  facet normal -1 0 0\n    outer loop\n"));
+        assert!(stl.ends_with("  endfacet\nendsolid cube\n"));
+        assert_eq!(stl.matches("facet normal").count(), 12);
+    }
+}
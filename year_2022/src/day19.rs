@@ -1,11 +1,11 @@
 use crate::day19::blueprint::BluePrint;
 use crate::day19::robot_factory::RobotFactory;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::str::FromStr;
+use std::fs::read_to_string;
+
+pub use crate::day19::report::{Minute, Report};
 
-mod bag;
 mod blueprint;
+mod report;
 mod resource;
 mod robot_factory;
 
@@ -13,36 +13,72 @@ pub struct Day19;
 
 impl Day19 {
     fn parse(path: &str) -> Vec<BluePrint> {
-        BufReader::new(File::open(path).unwrap())
-            .lines()
-            .map(|line| BluePrint::from_str(line.as_ref().unwrap()).unwrap())
-            .collect()
+        BluePrint::parse_all(&read_to_string(path).unwrap()).unwrap()
     }
 
-    pub fn part_one() -> usize {
-        let mut robot_factory = RobotFactory::default();
+    /// Best value of every blueprint in `minutes`, each searched on its own thread
+    fn blueprint_values(blueprints: &[BluePrint], minutes: u32) -> Vec<usize> {
+        std::thread::scope(|scope| {
+            blueprints
+                .iter()
+                .map(|blueprint| {
+                    scope.spawn(move || RobotFactory::new(blueprint).blue_print_value(minutes))
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
 
-        Self::parse("src/day19/input")
+    /// Most of `target` each blueprint of `path` can collect in `minutes`, by id.
+    ///
+    /// Blueprints may describe any resources and robots, and without a `target`
+    /// the resource of the last robot of each blueprint is maximized.
+    pub fn best_values(
+        path: &str,
+        minutes: u32,
+        target: Option<&str>,
+    ) -> Result<Vec<(usize, usize)>, String> {
+        let blueprints = Self::parse(path)
             .into_iter()
-            .enumerate()
-            .map(|(idx, blueprint)| {
-                let mut blueprint_value = 0;
-                robot_factory.blue_print_value(1, 25, &blueprint, 0, &mut blueprint_value);
-                (idx + 1) * blueprint_value
+            .map(|blueprint| match target {
+                Some(target) => blueprint.with_target(target),
+                None => Ok(blueprint),
             })
+            .collect::<Result<Vec<_>, _>>()?;
+        let values = Self::blueprint_values(&blueprints, minutes);
+
+        Ok(blueprints
+            .iter()
+            .map(|blueprint| blueprint.id)
+            .zip(values)
+            .collect())
+    }
+
+    /// Replay of the best plan of blueprint `id` of `path` over `minutes`
+    pub fn best_plan(path: &str, id: usize, minutes: u32) -> Option<Report> {
+        let blueprint = Self::parse(path)
+            .into_iter()
+            .find(|blueprint| blueprint.id == id)?;
+        let (_, builds) = RobotFactory::new(&blueprint).best_plan(minutes);
+
+        Some(Report::new(&blueprint, minutes, &builds))
+    }
+
+    pub fn part_one() -> usize {
+        Self::best_values("src/day19/input", 24, None)
+            .unwrap()
+            .into_iter()
+            .map(|(id, value)| id * value)
             .sum()
     }
 
     pub fn part_two() -> usize {
-        let mut robot_factory = RobotFactory::default();
         let blueprints = Self::parse("src/day19/input");
 
-        (0..3)
-            .map(|idx| {
-                let mut blueprint_value = 0;
-                robot_factory.blue_print_value(1, 33, &blueprints[idx], 0, &mut blueprint_value);
-                blueprint_value
-            })
+        Self::blueprint_values(&blueprints[..3], 32)
+            .into_iter()
             .product()
     }
 }
@@ -1,32 +1,187 @@
-use crate::day19::resource::Resource;
-use scan_fmt::scan_fmt;
-use std::collections::HashMap;
+use crate::day19::resource::{Resource, Resources};
+use crate::day19::robot_factory::MAX_RESOURCES;
 use std::str::FromStr;
-use strum::IntoEnumIterator;
 
-pub(crate) type Cost = HashMap<Resource, u32>;
+/// Amount of every resource, indexed by `Resource`
+pub(crate) type Cost = Vec<u32>;
 
-#[derive(Debug)]
+/// Robots harvesting `robot` cost `cost` to build
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Recipe {
+    pub(crate) robot: Resource,
+    pub(crate) cost: Cost,
+}
+
+/// Recipes for any number of robots, each costing any combination of resources.
+///
+/// The factory starts with a single robot of the first resource mentioned, and tries
+/// to collect as much as possible of `target`, by default the last robot described.
+#[derive(Debug, Clone)]
 pub(crate) struct BluePrint {
-    pub(crate) recipes: HashMap<Resource, Cost>,
+    pub(crate) id: usize,
+    pub(crate) resources: Resources,
+    pub(crate) recipes: Vec<Recipe>,
+    pub(crate) target: Resource,
     pub(crate) max_costs: Cost,
 }
 
+impl BluePrint {
+    /// Parses every blueprint of `s`, whether they are on one line each or spread over several
+    pub(crate) fn parse_all(s: &str) -> Result<Vec<Self>, String> {
+        s.split("Blueprint")
+            .filter(|blueprint| !blueprint.trim().is_empty())
+            .map(|blueprint| Self::from_str(&format!("Blueprint{}", blueprint)))
+            .collect()
+    }
+
+    /// Maximizes `resource` instead, failing if no robot harvests it
+    pub(crate) fn with_target(mut self, resource: &str) -> Result<Self, String> {
+        self.target = self
+            .resources
+            .index_of(resource)
+            .filter(|resource| self.recipes.iter().any(|recipe| recipe.robot == *resource))
+            .ok_or_else(|| format!("No robot collects {}", resource))?;
+
+        Ok(self)
+    }
+
+    /// Parses `Each <robot> robot costs <n> <resource>[, <n> <resource>][ and <n> <resource>]`
+    fn parse_recipe(
+        s: &str,
+        resources: &mut Resources,
+    ) -> Result<(Resource, Vec<(u32, Resource)>), String> {
+        let (robot, costs) = s
+            .trim()
+            .strip_prefix("Each ")
+            .and_then(|s| s.split_once(" robot costs "))
+            .ok_or_else(|| format!("Cannot parse recipe {}", s))?;
+        let robot = resources.intern(robot.trim());
+        let costs = costs
+            .split(" and ")
+            .flat_map(|costs| costs.split(", "))
+            .map(|cost| {
+                let (amount, resource) = cost
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("Cannot parse cost {}", cost))?;
+                let amount = amount
+                    .parse::<u32>()
+                    .map_err(|_| format!("Cannot parse amount {}", amount))?;
+
+                Ok((amount, resources.intern(resource.trim())))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok((robot, costs))
+    }
+}
+
 impl FromStr for BluePrint {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok((_, ore_robot_ore_cost, clay_robot_ore_cost, obsidian_robot_ore_cost, obsidian_robot_clay_cost, geode_robot_ore_cost, geode_robot_obsidian_cost)) = scan_fmt!(s, "Blueprint {d}: Each ore robot costs {d} ore. Each clay robot costs {d} ore. Each obsidian robot costs {d} ore and {d} clay. Each geode robot costs {d} ore and {d} obsidian.", u32, u32, u32, u32, u32, u32, u32) {
-            let recipes = HashMap::from([(Resource::Ore, HashMap::from([(Resource::Ore, ore_robot_ore_cost)])), (Resource::Clay, HashMap::from([(Resource::Ore, clay_robot_ore_cost)])), (Resource::Obsidian, HashMap::from([(Resource::Ore, obsidian_robot_ore_cost), (Resource::Clay, obsidian_robot_clay_cost)])), (Resource::Geode, HashMap::from([(Resource::Ore, geode_robot_ore_cost), (Resource::Obsidian, geode_robot_obsidian_cost)]))]);
-            let max_costs = Resource::iter().map(|resource| {
-                let max_cost_resource = recipes.values().map(|robot_costs| *robot_costs.get(&resource).unwrap_or(&0)).max().unwrap();
-                (resource, max_cost_resource)
-            }).collect();
-            return Ok(Self {
-                recipes, max_costs
-            });
+        let (header, body) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Missing blueprint header in {}", s))?;
+        let id = header
+            .trim()
+            .strip_prefix("Blueprint ")
+            .and_then(|id| id.parse::<usize>().ok())
+            .ok_or_else(|| format!("Cannot parse blueprint header {}", header))?;
+
+        let mut resources = Resources::default();
+        let parsed = body
+            .split('.')
+            .filter(|sentence| !sentence.trim().is_empty())
+            .map(|sentence| Self::parse_recipe(sentence, &mut resources))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if parsed.is_empty() {
+            return Err(format!("Blueprint {} has no recipes", id));
         }
 
-        Err(s.to_owned())
+        if resources.len() > MAX_RESOURCES {
+            return Err(format!(
+                "Blueprint {} uses {} resources, at most {} are supported",
+                id,
+                resources.len(),
+                MAX_RESOURCES
+            ));
+        }
+
+        // Costs can only be laid out once every resource is known
+        let recipes = parsed
+            .into_iter()
+            .map(|(robot, costs)| {
+                let mut cost = vec![0; resources.len()];
+                costs
+                    .into_iter()
+                    .for_each(|(amount, resource)| cost[resource] += amount);
+
+                Recipe { robot, cost }
+            })
+            .collect::<Vec<_>>();
+
+        let max_costs = (0..resources.len())
+            .map(|resource| {
+                recipes
+                    .iter()
+                    .map(|recipe| recipe.cost[resource])
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+
+        Ok(Self {
+            id,
+            target: recipes.last().unwrap().robot,
+            resources,
+            recipes,
+            max_costs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_puzzle_blueprints() {
+        let blueprints =
+            BluePrint::parse_all(&std::fs::read_to_string("src/day19/test").unwrap()).unwrap();
+        let blueprint = &blueprints[0];
+
+        assert_eq!(blueprints.len(), 2);
+        assert_eq!(blueprint.id, 1);
+        assert_eq!(blueprint.resources.name(blueprint.target), "geode");
+        assert_eq!(
+            blueprint.recipes[2],
+            Recipe {
+                robot: 2,
+                cost: vec![3, 14, 0, 0]
+            }
+        );
+        assert_eq!(blueprint.max_costs, vec![4, 14, 7, 0]);
+    }
+
+    #[test]
+    fn parse_custom_resources() {
+        let blueprint = BluePrint::from_str(
+            "Blueprint 7:
+  Each wood robot costs 1 wood.
+  Each stone robot costs 2 wood.
+  Each gem robot costs 3 wood, 2 stone and 1 dust.
+  Each dust robot costs 4 stone.",
+        )
+        .unwrap()
+        .with_target("gem")
+        .unwrap();
+
+        assert_eq!(blueprint.id, 7);
+        assert_eq!(blueprint.resources.len(), 4);
+        assert_eq!(blueprint.resources.name(blueprint.target), "gem");
+        assert_eq!(blueprint.recipes[2].cost, vec![3, 2, 0, 1]);
+        assert!(BluePrint::from_str("Blueprint 1: Each ore robot costs ore.").is_err());
     }
 }
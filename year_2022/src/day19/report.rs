@@ -0,0 +1,198 @@
+use crate::day19::blueprint::BluePrint;
+use std::fmt::{Display, Formatter};
+
+/// What happened during one minute of a plan, amounts being indexed by resource
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Minute {
+    pub minute: u32,
+    /// Resources spent and the resource collected by the robot started this minute
+    pub built: Option<(Vec<u32>, usize)>,
+    /// Robots harvesting during the minute, the new one not included
+    pub robots: Vec<u32>,
+    /// Stock at the end of the minute
+    pub inventory: Vec<u32>,
+}
+
+/// Minute by minute replay of a build order
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Report {
+    pub blueprint: usize,
+    pub resources: Vec<String>,
+    pub minutes: Vec<Minute>,
+}
+
+impl Report {
+    /// Replays `builds`, the minute each robot is started with the index of its recipe
+    pub(crate) fn new(blueprint: &BluePrint, minutes: u32, builds: &[(u32, usize)]) -> Self {
+        let resources = (0..blueprint.resources.len())
+            .map(|resource| blueprint.resources.name(resource).to_owned())
+            .collect::<Vec<_>>();
+        let mut robots = vec![0; resources.len()];
+        let mut inventory = vec![0; resources.len()];
+        let mut builds = builds.iter().peekable();
+        robots[0] = 1;
+
+        let minutes = (1..=minutes)
+            .map(|minute| {
+                let built = builds.next_if(|(at, _)| *at == minute).map(|(_, i)| {
+                    let recipe = &blueprint.recipes[*i];
+
+                    inventory
+                        .iter_mut()
+                        .zip(&recipe.cost)
+                        .for_each(|(stock, cost)| *stock -= cost);
+
+                    (recipe.cost.clone(), recipe.robot)
+                });
+
+                inventory
+                    .iter_mut()
+                    .zip(&robots)
+                    .for_each(|(stock, robots)| *stock += robots);
+
+                let report = Minute {
+                    minute,
+                    built: built.clone(),
+                    robots: robots.clone(),
+                    inventory: inventory.clone(),
+                };
+
+                if let Some((_, robot)) = built {
+                    robots[robot] += 1;
+                }
+
+                report
+            })
+            .collect();
+
+        Self {
+            blueprint: blueprint.id,
+            resources,
+            minutes,
+        }
+    }
+
+    fn plural(count: u32, singular: &str, plural: &str) -> String {
+        format!("{} {}", count, if count == 1 { singular } else { plural })
+    }
+
+    /// `1 ore`, `2 clay`, `1 geode` or `2 geodes`, geodes being the only countable resource
+    fn amount(&self, count: u32, resource: usize) -> String {
+        match self.resources[resource].as_str() {
+            "geode" => Self::plural(count, "geode", "geodes"),
+            name => format!("{} {}", count, name),
+        }
+    }
+
+    fn robot(&self, resource: usize) -> String {
+        match self.resources[resource].as_str() {
+            "geode" => "geode-cracking robot".to_owned(),
+            name => format!("{}-collecting robot", name),
+        }
+    }
+}
+
+impl Display for Report {
+    /// Same narrative as the puzzle
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, minute) in self.minutes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "== Minute {} ==", minute.minute)?;
+
+            if let Some((cost, robot)) = &minute.built {
+                let spent = cost
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, amount)| **amount > 0)
+                    .map(|(resource, amount)| self.amount(*amount, resource))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+
+                writeln!(
+                    f,
+                    "Spend {} to start building a {}.",
+                    spent,
+                    self.robot(*robot)
+                )?;
+            }
+
+            for (resource, count) in minute.robots.iter().enumerate() {
+                if *count == 0 {
+                    continue;
+                }
+
+                let robots = Self::plural(
+                    *count,
+                    &self.robot(resource),
+                    &format!("{}s", self.robot(resource)),
+                );
+                let stock = minute.inventory[resource];
+
+                if self.resources[resource] == "geode" {
+                    let verb = if *count == 1 { "cracks" } else { "crack" };
+                    let open = Self::plural(stock, "open geode", "open geodes");
+
+                    writeln!(
+                        f,
+                        "{} {} {}; you now have {}.",
+                        robots,
+                        verb,
+                        self.amount(*count, resource),
+                        open
+                    )?;
+                } else {
+                    let verb = if *count == 1 { "collects" } else { "collect" };
+
+                    writeln!(
+                        f,
+                        "{} {} {}; you now have {}.",
+                        robots,
+                        verb,
+                        self.amount(*count, resource),
+                        self.amount(stock, resource)
+                    )?;
+                }
+            }
+
+            if let Some((_, robot)) = &minute.built {
+                writeln!(
+                    f,
+                    "The new {} is ready; you now have {} of them.",
+                    self.robot(*robot),
+                    minute.robots[*robot] + 1
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day19::robot_factory::RobotFactory;
+
+    #[test]
+    fn puzzle_narrative() {
+        let blueprints =
+            BluePrint::parse_all(&std::fs::read_to_string("src/day19/test").unwrap()).unwrap();
+        let (value, builds) = RobotFactory::new(&blueprints[0]).best_plan(24);
+        let report = Report::new(&blueprints[0], 24, &builds);
+        let text = report.to_string();
+
+        assert_eq!(value, 9);
+        assert_eq!(report.minutes.last().unwrap().inventory[3], 9);
+        assert!(text.starts_with(
+            "== Minute 1 ==\n1 ore-collecting robot collects 1 ore; you now have 1 ore.\n\n"
+        ));
+        assert!(text.contains("Spend 2 ore to start building a clay-collecting robot.\n"));
+        assert!(
+            text.contains("Spend 2 ore and 7 obsidian to start building a geode-cracking robot.\n")
+        );
+        assert!(text.ends_with("crack 2 geodes; you now have 9 open geodes.\n"));
+    }
+}
@@ -1,9 +1,31 @@
-use strum_macros::EnumIter;
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash, EnumIter, PartialOrd, Ord)]
-pub(crate) enum Resource {
-    Ore,
-    Clay,
-    Obsidian,
-    Geode,
+/// Index of a resource in the `Resources` of a blueprint
+pub(crate) type Resource = usize;
+
+/// Names of the resources a blueprint knows about, in order of first appearance
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct Resources(Vec<String>);
+
+impl Resources {
+    /// Index of `name`, registering it if it was never seen before
+    pub(crate) fn intern(&mut self, name: &str) -> Resource {
+        match self.index_of(name) {
+            Some(resource) => resource,
+            None => {
+                self.0.push(name.to_owned());
+                self.0.len() - 1
+            }
+        }
+    }
+
+    pub(crate) fn index_of(&self, name: &str) -> Option<Resource> {
+        self.0.iter().position(|known| known == name)
+    }
+
+    pub(crate) fn name(&self, resource: Resource) -> &str {
+        &self.0[resource]
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
 }
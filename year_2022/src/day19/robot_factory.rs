@@ -1,126 +1,234 @@
-use crate::day19::bag::Bag;
-use crate::day19::blueprint::{BluePrint, Cost};
+use crate::day19::blueprint::BluePrint;
 use crate::day19::resource::Resource;
-use std::collections::HashMap;
-use strum::IntoEnumIterator;
+use std::collections::HashSet;
 
-pub(crate) type RobotInventory = Cost;
+/// Most resources a blueprint can use, so states fit in fixed size arrays
+pub(crate) const MAX_RESOURCES: usize = 8;
 
-#[derive(Debug)]
+pub(crate) type Amounts = [u32; MAX_RESOURCES];
+
+/// Robots and stock of the factory with `time_left` minutes to go
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub(crate) struct State {
+    pub(crate) time_left: u32,
+    pub(crate) robots: Amounts,
+    pub(crate) stock: Amounts,
+}
+
+/// Searches the best build order of a blueprint.
+///
+/// Instead of branching on every minute, each step picks the next robot to build and
+/// skips straight to the minute it can be afforded. Branches are cut when even building
+/// every affordable robot each minute for free cannot beat the best value found so far,
+/// and states that only differ by stock too large to ever be spent are visited once.
 pub(crate) struct RobotFactory {
-    pub(crate) robots: RobotInventory,
-    pub(crate) stock: Bag,
+    recipes: Vec<(Resource, Amounts)>,
+    max_costs: Amounts,
+    target: Resource,
+    seen: HashSet<State>,
+    best: u32,
+
+    // Robots built so far and in the best plan, as (minute, recipe index)
+    path: Vec<(u32, usize)>,
+    best_path: Vec<(u32, usize)>,
+    minutes: u32,
 }
 
-impl Default for RobotFactory {
-    fn default() -> Self {
-        let mut inventory = HashMap::from_iter(Resource::iter().map(|resource| (resource, 0)));
-        *inventory.get_mut(&Resource::Ore).unwrap() = 1;
+impl RobotFactory {
+    pub(crate) fn new(blue_print: &BluePrint) -> Self {
+        let amounts = |values: &[u32]| {
+            let mut amounts = [0; MAX_RESOURCES];
+            amounts[..values.len()].copy_from_slice(values);
+            amounts
+        };
 
         Self {
-            robots: inventory,
-            stock: Default::default(),
+            recipes: blue_print
+                .recipes
+                .iter()
+                .map(|recipe| (recipe.robot, amounts(&recipe.cost)))
+                .collect(),
+            max_costs: amounts(&blue_print.max_costs),
+            target: blue_print.target,
+            seen: HashSet::new(),
+            best: 0,
+            path: vec![],
+            best_path: vec![],
+            minutes: 0,
         }
     }
-}
-impl RobotFactory {
-    pub(crate) fn can_build(&self, blue_print: &BluePrint, robot_type: &Resource) -> bool {
-        blue_print.recipes[robot_type]
-            .iter()
-            .all(|(resource, amount_required)| self.stock.resources[resource] >= *amount_required)
+
+    /// Most of the target resource that can be collected in `minutes`, starting with a
+    /// single robot of the first resource
+    pub(crate) fn blue_print_value(&mut self, minutes: u32) -> usize {
+        self.best_plan(minutes).0
     }
 
-    pub(crate) fn build(&mut self, blue_print: &BluePrint, robot_type: &Resource) {
-        blue_print.recipes[robot_type]
-            .iter()
-            .for_each(|(resource, amount_required)| {
-                (*self.stock.resources.get_mut(resource).unwrap()) -= *amount_required;
-            });
+    /// Best value together with the robots to build for it, as the minute each one
+    /// starts being built and the index of its recipe
+    pub(crate) fn best_plan(&mut self, minutes: u32) -> (usize, Vec<(u32, usize)>) {
+        let mut robots = [0; MAX_RESOURCES];
+        robots[0] = 1;
+
+        self.seen.clear();
+        self.best = 0;
+        self.path.clear();
+        self.best_path.clear();
+        self.minutes = minutes;
+        self.search(State {
+            time_left: minutes,
+            robots,
+            stock: [0; MAX_RESOURCES],
+        });
 
-        (*self.robots.get_mut(robot_type).unwrap()) += 1;
+        (self.best as usize, self.best_path.clone())
     }
 
-    pub(crate) fn reverse_build(&mut self, blue_print: &BluePrint, robot_type: &Resource) {
-        blue_print.recipes[robot_type]
-            .iter()
-            .for_each(|(resource, amount_required)| {
-                (*self.stock.resources.get_mut(resource).unwrap()) += *amount_required;
-            });
+    fn search(&mut self, state: State) {
+        let target = self.target;
+        let idle = state.stock[target] + state.robots[target] * state.time_left;
 
-        (*self.robots.get_mut(robot_type).unwrap()) -= 1;
-    }
+        if idle > self.best {
+            self.best = idle;
+            self.best_path.clone_from(&self.path);
+        }
+
+        let state = self.capped(state);
 
-    pub(crate) fn blue_print_value(
-        &mut self,
-        moment: u8,
-        limit: u8,
-        blueprint: &BluePrint,
-        acc: usize,
-        result: &mut usize,
-    ) {
-        if moment == limit {
-            *result = (*result).max(acc);
+        if self.upper_bound(&state) <= self.best || !self.seen.insert(state) {
             return;
         }
 
-        if acc
-            + self.robots[&Resource::Geode] as usize
-            + (limit - moment) as usize * (limit + moment) as usize / 2
-            < *result
-        {
-            return;
+        for i in (0..self.recipes.len()).rev() {
+            if let Some(next) = self.build_next(&state, i) {
+                // The robot is paid for during the minute before it starts harvesting
+                self.path.push((self.minutes - next.time_left, i));
+                self.search(next);
+                self.path.pop();
+            }
         }
+    }
+
+    /// Waits until recipe `i` is affordable and builds it, if that leaves the robot
+    /// at least one minute to harvest
+    fn build_next(&self, state: &State, i: usize) -> Option<State> {
+        let (robot, cost) = &self.recipes[i];
+
+        // One more robot than the most a minute can consume is useless
+        if *robot != self.target && state.robots[*robot] >= self.max_costs[*robot] {
+            return None;
+        }
+
+        let mut wait = 0;
 
-        let buildable_robots = Resource::iter()
-            .rev()
-            .filter(|robot_type| {
-                self.can_build(blueprint, robot_type) && {
-                    match *robot_type {
-                        Resource::Geode => true,
-                        _ => self.stock.resources[robot_type] <= blueprint.max_costs[robot_type],
-                    }
+        for ((cost, stock), robots) in cost.iter().zip(&state.stock).zip(&state.robots) {
+            if cost > stock {
+                if *robots == 0 {
+                    return None;
                 }
-            })
-            .collect::<Vec<_>>();
-
-        let harvested_resources = self
-            .robots
-            .iter()
-            .map(|(resource, num_robots)| (resource.clone(), *num_robots))
-            .collect::<HashMap<Resource, u32>>();
-
-        harvested_resources
-            .iter()
-            .for_each(|(resource, num_robots)| {
-                (*self.stock.resources.get_mut(&resource).unwrap()) += num_robots;
-            });
-
-        self.blue_print_value(
-            moment + 1,
-            limit,
-            blueprint,
-            acc + self.robots[&Resource::Geode] as usize,
-            result,
-        );
-
-        buildable_robots.into_iter().for_each(|robot_type| {
-            self.build(blueprint, &robot_type);
-
-            self.blue_print_value(
-                moment + 1,
-                limit,
-                blueprint,
-                acc + self.robots[&Resource::Geode] as usize
-                    - (robot_type == Resource::Geode) as usize,
-                result,
-            );
-
-            // Try building anything else
-            self.reverse_build(blueprint, &robot_type);
-        });
 
-        harvested_resources.iter().for_each(|(k, v)| {
-            (*self.stock.resources.get_mut(&k).unwrap()) -= v;
-        });
+                wait = wait.max((cost - stock).div_ceil(*robots));
+            }
+        }
+
+        if wait + 1 >= state.time_left {
+            return None;
+        }
+
+        let mut next = *state;
+        next.time_left -= wait + 1;
+
+        for ((stock, robots), cost) in next.stock.iter_mut().zip(&state.robots).zip(cost) {
+            *stock = *stock + robots * (wait + 1) - cost;
+        }
+
+        next.robots[*robot] += 1;
+
+        Some(next)
+    }
+
+    /// Target collected if every recipe had its own copy of the stock to pay from.
+    ///
+    /// Each copy receives the whole production but only pays for its own recipe, so it
+    /// never holds less than the real stock, and every recipe is built as soon as its
+    /// copy affords it, at most once a minute like in the real factory.
+    fn upper_bound(&self, state: &State) -> u32 {
+        let mut robots = state.robots;
+        let mut pools = vec![state.stock; self.recipes.len()];
+        let mut collected = state.stock[self.target];
+
+        for _ in 0..state.time_left {
+            let mut built = [0; MAX_RESOURCES];
+
+            for ((robot, cost), pool) in self.recipes.iter().zip(pools.iter_mut()) {
+                if cost
+                    .iter()
+                    .zip(pool.iter())
+                    .all(|(cost, stock)| cost <= stock)
+                {
+                    pool.iter_mut()
+                        .zip(cost)
+                        .for_each(|(stock, cost)| *stock -= cost);
+                    built[*robot] += 1;
+                }
+
+                pool.iter_mut()
+                    .zip(&robots)
+                    .for_each(|(stock, robots)| *stock += robots);
+            }
+
+            collected += robots[self.target];
+
+            for resource in 0..MAX_RESOURCES {
+                robots[resource] += built[resource];
+            }
+        }
+
+        collected
+    }
+
+    /// Same state with any stock beyond what the remaining minutes can spend dropped
+    fn capped(&self, mut state: State) -> State {
+        for resource in 0..MAX_RESOURCES {
+            if resource != self.target {
+                state.stock[resource] =
+                    state.stock[resource].min(self.max_costs[resource] * state.time_left);
+            }
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn example() {
+        let blueprints =
+            BluePrint::parse_all(&std::fs::read_to_string("src/day19/test").unwrap()).unwrap();
+        let values = |minutes| {
+            blueprints
+                .iter()
+                .map(|blueprint| RobotFactory::new(blueprint).blue_print_value(minutes))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(24), vec![9, 12]);
+        assert_eq!(values(32), vec![56, 62]);
+    }
+
+    #[test]
+    fn target_used_in_recipes() {
+        // Gem robots need gems, and nothing produces the first one
+        let blueprint = BluePrint::from_str(
+            "Blueprint 1: Each wood robot costs 1 wood. Each gem robot costs 2 wood and 1 gem.",
+        )
+        .unwrap();
+        let mut factory = RobotFactory::new(&blueprint);
+
+        assert_eq!(factory.blue_print_value(10), 0);
     }
 }
@@ -16,26 +16,12 @@ impl Day20 {
         );
         shifter.shift_rounds(rounds);
 
-        let zero_element = shifter
-            .positions
-            .iter()
-            .find(|position| (*position).1 == 0)
-            .unwrap();
+        let mixed = Vec::<i64>::from(&shifter);
+        let zero_position = mixed.iter().position(|value| *value == 0).unwrap();
 
         [1_000, 2_000, 3_000]
             .into_iter()
-            .map(|offset| {
-                shifter
-                    .positions
-                    .iter()
-                    .find(|position| {
-                        (*position).0 as i64
-                            == (zero_element.0 as i64 + offset)
-                                .rem_euclid(shifter.positions.len() as i64)
-                    })
-                    .unwrap()
-                    .1
-            })
+            .map(|offset| mixed[(zero_position + offset) % mixed.len()])
             .sum()
     }
 
@@ -1,82 +1,111 @@
-/// Tuple that keeps index now, value, and original index.
+/// Keeps the values in their current order using sqrt decomposition.
 ///
-/// We need both index now and original index to know the same
-/// order of processing items
-#[derive(Debug, Clone)]
-pub(crate) struct IndexValuePair(pub(crate) usize, pub(crate) i64);
-
+/// Items are identified by their original index, which is also the order
+/// in which they are processed. The current order is split into blocks of
+/// roughly `sqrt(n)` items, so finding, removing and inserting an item
+/// only costs `O(sqrt(n))` instead of renumbering the whole list.
 #[derive(Debug, Clone)]
 pub(crate) struct Mixer {
-    pub(crate) positions: Vec<IndexValuePair>,
+    values: Vec<i64>,
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+    block_size: usize,
 }
 
 impl Mixer {
     pub(crate) fn new(values: Vec<i64>) -> Self {
-        Self {
-            positions: values
-                .into_iter()
-                .enumerate()
-                .map(|(idx, value)| IndexValuePair(idx, value))
-                .collect(),
+        let block_size = ((values.len() as f64).sqrt() as usize).max(1);
+        let mut mixer = Self {
+            blocks: vec![(0..values.len()).collect()],
+            block_of: vec![0; values.len()],
+            values,
+            block_size,
+        };
+
+        mixer.rebuild();
+        mixer
+    }
+
+    /// Splits the current order into blocks of `block_size` items again
+    fn rebuild(&mut self) {
+        let order = self.blocks.concat();
+
+        self.blocks = order
+            .chunks(self.block_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+
+        self.blocks.iter().enumerate().for_each(|(block, items)| {
+            items.iter().for_each(|idx| self.block_of[*idx] = block);
+        });
+    }
+
+    fn remove(&mut self, idx: usize) -> usize {
+        let block = self.block_of[idx];
+        let offset = self.blocks[block]
+            .iter()
+            .position(|item| *item == idx)
+            .unwrap();
+
+        self.blocks[block].remove(offset);
+        self.blocks[..block]
+            .iter()
+            .map(|items| items.len())
+            .sum::<usize>()
+            + offset
+    }
+
+    fn insert(&mut self, idx: usize, mut position: usize) {
+        let mut block = 0;
+
+        // Inserting right after the last item of a block is allowed,
+        // which also covers appending at the very end of the list
+        while position > self.blocks[block].len() {
+            position -= self.blocks[block].len();
+            block += 1;
+        }
+
+        self.blocks[block].insert(position, idx);
+        self.block_of[idx] = block;
+
+        if self.blocks[block].len() > 2 * self.block_size {
+            self.rebuild();
         }
     }
 
     fn shift(&mut self, idx: usize) {
-        let length = self.positions.len();
-        let element_to_update = &self.positions[idx];
-        let old_position = element_to_update.0;
-        let mut new_position = (element_to_update.0 as i64 + element_to_update.1)
-            .rem_euclid(length as i64 - 1) as usize;
-
-        if new_position == 0 {
-            new_position = self.positions.len() - 1;
-        }
+        let length = self.values.len();
 
-        // The new position does not change as the offset is a multiple of list length
-        if new_position == old_position {
+        if length < 2 {
             return;
         }
 
-        if new_position < old_position {
-            self.positions
-                .iter_mut()
-                .rev()
-                .filter(|tuple| tuple.0 >= new_position && tuple.0 < old_position)
-                .for_each(|tuple| tuple.0 += 1);
-        } else {
-            self.positions
-                .iter_mut()
-                .filter(|tuple| tuple.0 <= new_position && tuple.0 > old_position)
-                .for_each(|tuple| tuple.0 -= 1);
-        }
+        let old_position = self.remove(idx);
+
+        // Once removed, the item moves over the remaining `length - 1` items
+        let new_position =
+            (old_position as i64 + self.values[idx]).rem_euclid(length as i64 - 1) as usize;
 
-        self.positions.get_mut(idx).unwrap().0 = new_position;
+        self.insert(idx, new_position);
     }
 
     pub(crate) fn shift_rounds(&mut self, rounds: usize) {
         (0..rounds).for_each(|_| {
-            (0..self.positions.len()).for_each(|i| {
+            (0..self.values.len()).for_each(|i| {
                 self.shift(i);
             });
         })
     }
+
+    /// Values in their current order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.blocks.iter().flatten().map(|idx| self.values[*idx])
+    }
 }
 
 impl From<&Mixer> for Vec<i64> {
     fn from(shifter: &Mixer) -> Self {
-        let mut result = Vec::with_capacity(shifter.positions.len());
-
-        unsafe {
-            shifter.positions.iter().for_each(|position| {
-                (result.as_mut_ptr() as *mut i64)
-                    .offset(position.0 as isize)
-                    .write(position.1);
-            });
-
-            result.set_len(result.capacity());
-        }
-
-        result
+        shifter.iter().collect()
     }
 }
 
@@ -137,4 +166,13 @@ mod tests {
         shifter.shift(6);
         assert_eq!(Vec::<i64>::from(&shifter), vec![1, 2, -3, 3, 4, -2, 0]);
     }
+
+    #[test]
+    fn shift_rounds_across_blocks() {
+        let mut shifter = Mixer::new(vec![1, 2, -3, 3, -2, 0, 4]);
+
+        // Blocks hold 2 items, so items keep moving between blocks
+        shifter.shift_rounds(1);
+        assert_eq!(Vec::<i64>::from(&shifter), vec![-2, 1, 2, -3, 4, 0, 3]);
+    }
 }
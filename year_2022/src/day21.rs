@@ -1,18 +1,33 @@
 use crate::day21::jungle::Jungle;
 
+mod error;
 mod expression;
 mod jungle;
+mod linear;
+mod rational;
 
 pub struct Day21;
 
 impl Day21 {
     pub fn part_one() -> i64 {
         let jungle = Jungle::parse("src/day21/input");
-        jungle.eval("root")
+        jungle.eval("root").unwrap().to_integer().unwrap() as i64
     }
 
     pub fn part_two() -> i64 {
         let jungle = Jungle::parse("src/day21/input");
-        jungle.solve("root")
+        jungle.solve("root", "humn").unwrap().to_integer().unwrap() as i64
+    }
+
+    /// The equality `root` checks, with `humn` as the unknown
+    pub fn equation() -> String {
+        let jungle = Jungle::parse("src/day21/input");
+        jungle.equation("root", "humn").unwrap()
+    }
+
+    /// Graphviz DOT export of the monkeys and the ones they wait for
+    pub fn dependency_graph() -> String {
+        let jungle = Jungle::parse("src/day21/input");
+        jungle.to_dot()
     }
 }
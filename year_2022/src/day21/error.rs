@@ -0,0 +1,34 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum SolveError {
+    UnknownMonkey(String),
+    Cycle(String),
+    NotAnEquation(String),
+    NonLinear(String),
+    DivisionByZero(String),
+    Overflow(String),
+    NoSolution,
+    InfiniteSolutions,
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::UnknownMonkey(name) => write!(f, "Unknown monkey {}", name),
+            SolveError::Cycle(name) => write!(f, "Monkey {} depends on itself", name),
+            SolveError::NotAnEquation(name) => {
+                write!(f, "Monkey {} does not compare two monkeys", name)
+            }
+            SolveError::NonLinear(name) => {
+                write!(f, "Monkey {} is not linear in the unknown", name)
+            }
+            SolveError::DivisionByZero(name) => write!(f, "Monkey {} divides by zero", name),
+            SolveError::Overflow(name) => {
+                write!(f, "Monkey {} yells a number too large to compute", name)
+            }
+            SolveError::NoSolution => write!(f, "The equation has no solution"),
+            SolveError::InfiniteSolutions => write!(f, "Any value solves the equation"),
+        }
+    }
+}
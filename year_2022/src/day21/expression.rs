@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 #[derive(Debug)]
 pub(crate) enum Expression {
     Add(String, String),
@@ -8,6 +10,8 @@ pub(crate) enum Expression {
 }
 
 impl Expression {
+    pub(crate) const ATOM: u8 = 3;
+
     pub(crate) fn dependencies(&self) -> Option<[&str; 2]> {
         match self {
             Expression::Add(m1, m2)
@@ -17,4 +21,39 @@ impl Expression {
             Expression::Value(_) => None,
         }
     }
+
+    pub(crate) fn operator(&self) -> char {
+        match self {
+            Expression::Add(_, _) => '+',
+            Expression::Sub(_, _) => '-',
+            Expression::Mul(_, _) => '*',
+            Expression::Div(_, _) => '/',
+            Expression::Value(_) => unreachable!(),
+        }
+    }
+
+    /// Binding strength used to decide where infix text needs parentheses
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Expression::Add(_, _) | Expression::Sub(_, _) => 1,
+            Expression::Mul(_, _) | Expression::Div(_, _) => 2,
+            Expression::Value(_) => Self::ATOM,
+        }
+    }
+
+    pub(crate) fn is_associative(&self) -> bool {
+        matches!(self, Expression::Add(_, _) | Expression::Mul(_, _))
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Add(m1, m2)
+            | Expression::Sub(m1, m2)
+            | Expression::Mul(m1, m2)
+            | Expression::Div(m1, m2) => write!(f, "{} {} {}", m1, self.operator(), m2),
+            Expression::Value(value) => write!(f, "{}", value),
+        }
+    }
 }
@@ -1,8 +1,12 @@
+use crate::day21::error::SolveError;
 use crate::day21::expression::Expression;
+use crate::day21::linear::Linear;
+use crate::day21::rational::Rational;
+use itertools::Itertools;
 use scan_fmt::scan_fmt;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub(crate) struct Monkey {
@@ -20,183 +24,393 @@ pub(crate) struct Jungle {
     monkeys: HashMap<String, Monkey>,
 }
 
+impl FromStr for Jungle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut monkeys = HashMap::new();
+
+        for content in s.lines() {
+            if let Ok((source_monkey, monkey_dep1, op, monkey_dep2)) =
+                scan_fmt!(content, r"{}: {} {} {}", String, String, String, String)
+            {
+                let job = match op.as_str() {
+                    "+" => Expression::Add(monkey_dep1, monkey_dep2),
+                    "-" => Expression::Sub(monkey_dep1, monkey_dep2),
+                    "/" => Expression::Div(monkey_dep1, monkey_dep2),
+                    "*" => Expression::Mul(monkey_dep1, monkey_dep2),
+                    _ => return Err(format!("Unknown {}", content)),
+                };
+
+                monkeys.insert(source_monkey, Monkey::new(job));
+            } else if let Ok((source_monkey, value)) = scan_fmt!(content, "{}: {d}", String, i64) {
+                monkeys.insert(source_monkey, Monkey::new(Expression::Value(value)));
+            } else {
+                return Err(format!("Cannot parse {}", content));
+            }
+        }
+
+        Ok(Self { monkeys })
+    }
+}
+
 impl Jungle {
     pub(crate) fn parse(path: &str) -> Self {
-        let mut monkeys = HashMap::new();
+        Self::from_str(&read_to_string(path).unwrap()).unwrap()
+    }
 
-        BufReader::new(File::open(path).unwrap())
-            .lines()
-            .for_each(|line| {
-                let content = line.as_ref().unwrap();
-                if let Ok((source_monkey, monkey_dep1, op, monkey_dep2)) =
-                    scan_fmt!(content, r"{}: {} {} {}", String, String, String, String)
-                {
-                    match op.as_str() {
-                        "+" => monkeys.insert(
-                            source_monkey,
-                            Monkey::new(Expression::Add(monkey_dep1, monkey_dep2)),
-                        ),
-                        "-" => monkeys.insert(
-                            source_monkey,
-                            Monkey::new(Expression::Sub(monkey_dep1, monkey_dep2)),
-                        ),
-                        "/" => monkeys.insert(
-                            source_monkey,
-                            Monkey::new(Expression::Div(monkey_dep1, monkey_dep2)),
-                        ),
-                        "*" => monkeys.insert(
-                            source_monkey,
-                            Monkey::new(Expression::Mul(monkey_dep1, monkey_dep2)),
-                        ),
-                        _ => panic!("Unknown {}", content),
-                    };
-                }
+    /// Lists the monkeys `monkey_name` depends on, dependencies first.
+    ///
+    /// Uses an explicit stack so deep chains cannot overflow the call stack,
+    /// and reports a cycle as soon as a monkey is reached again from its own dependencies.
+    /// `variable` is a leaf, whatever its job is.
+    fn post_order<'a>(
+        &'a self,
+        monkey_name: &'a str,
+        variable: Option<&str>,
+    ) -> Result<Vec<&'a str>, SolveError> {
+        let mut order = vec![];
+        let mut done = HashSet::new();
+        let mut visiting = HashSet::new();
+        let mut stack = vec![(monkey_name, false)];
+
+        while let Some((name, expanded)) = stack.pop() {
+            if expanded {
+                visiting.remove(name);
+                done.insert(name);
+                order.push(name);
+                continue;
+            }
+
+            if done.contains(name) {
+                continue;
+            }
+
+            // Only the monkeys on the current path are still being visited
+            if visiting.contains(name) {
+                return Err(SolveError::Cycle(name.to_owned()));
+            }
 
-                if let Ok((source_monkey, value)) = scan_fmt!(content, "{}: {d}", String, i64) {
-                    monkeys.insert(source_monkey, Monkey::new(Expression::Value(value)));
+            let monkey = self
+                .monkeys
+                .get(name)
+                .ok_or_else(|| SolveError::UnknownMonkey(name.to_owned()))?;
+
+            visiting.insert(name);
+            stack.push((name, true));
+
+            if Some(name) != variable {
+                if let Some(dependencies) = monkey.job.dependencies() {
+                    stack.extend(dependencies.into_iter().rev().map(|dep| (dep, false)));
                 }
-            });
+            }
+        }
 
-        Self { monkeys }
+        Ok(order)
     }
 
-    pub(crate) fn eval(&self, monkey_name: &str) -> i64 {
-        match &self.monkeys[monkey_name].job {
-            Expression::Add(m1, m2) => self.eval(&m1) + self.eval(&m2),
-            Expression::Sub(m1, m2) => self.eval(&m1) - self.eval(&m2),
-            Expression::Mul(m1, m2) => self.eval(&m1) * self.eval(&m2),
-            Expression::Div(m1, m2) => self.eval(&m1) / self.eval(&m2),
-            Expression::Value(m) => *m,
+    /// Turns every monkey `monkey_name` depends on into `a * variable + b`,
+    /// `variable` being an unknown regardless of the job the monkey with that name has
+    fn compile_all<'a>(
+        &'a self,
+        monkey_name: &'a str,
+        variable: Option<&str>,
+    ) -> Result<HashMap<&'a str, Linear>, SolveError> {
+        let mut values: HashMap<&str, Linear> = HashMap::new();
+
+        for name in self.post_order(monkey_name, variable)? {
+            let value = match &self.monkeys[name].job {
+                _ if Some(name) == variable => Some(Linear::variable()),
+                Expression::Add(m1, m2) => values[m1.as_str()].checked_add(values[m2.as_str()]),
+                Expression::Sub(m1, m2) => values[m1.as_str()].checked_sub(values[m2.as_str()]),
+                Expression::Mul(m1, m2) => {
+                    let (left, right) = (values[m1.as_str()], values[m2.as_str()]);
+
+                    match (left.as_constant(), right.as_constant()) {
+                        (Some(factor), _) => right.checked_scale(factor),
+                        (_, Some(factor)) => left.checked_scale(factor),
+                        _ => return Err(SolveError::NonLinear(name.to_owned())),
+                    }
+                }
+                Expression::Div(m1, m2) => match values[m2.as_str()].as_constant() {
+                    None => return Err(SolveError::NonLinear(name.to_owned())),
+                    Some(divisor) if divisor.is_zero() => {
+                        return Err(SolveError::DivisionByZero(name.to_owned()))
+                    }
+                    Some(divisor) => Rational::ONE
+                        .checked_div(divisor)
+                        .and_then(|factor| values[m1.as_str()].checked_scale(factor)),
+                },
+                Expression::Value(value) => Some(Linear::constant(Rational::from(*value))),
+            }
+            .ok_or_else(|| SolveError::Overflow(name.to_owned()))?;
+
+            values.insert(name, value);
         }
+
+        Ok(values)
     }
 
-    fn eval_evaluable_expressions(
+    pub(crate) fn compile(
         &self,
         monkey_name: &str,
-        results: &mut HashMap<String, i64>,
-    ) -> Option<i64> {
-        if monkey_name == "humn" {
-            return None;
-        }
-
-        match &self.monkeys[monkey_name].job {
-            Expression::Add(m1, m2) => {
-                let r1 = self.eval_evaluable_expressions(m1, results);
-                let r2 = self.eval_evaluable_expressions(m2, results);
+        variable: Option<&str>,
+    ) -> Result<Linear, SolveError> {
+        Ok(self.compile_all(monkey_name, variable)?[monkey_name])
+    }
 
-                if r1.is_none() || r2.is_none() {
-                    return None;
-                }
+    pub(crate) fn eval(&self, monkey_name: &str) -> Result<Rational, SolveError> {
+        Ok(self.compile(monkey_name, None)?.constant)
+    }
 
-                results.insert(monkey_name.to_owned(), r1.unwrap() + r2.unwrap());
-            }
-            Expression::Sub(m1, m2) => {
-                let r1 = self.eval_evaluable_expressions(m1, results);
-                let r2 = self.eval_evaluable_expressions(m2, results);
+    /// Finds the value of `variable` for which both operands of `monkey_name` are equal
+    pub(crate) fn solve(&self, monkey_name: &str, variable: &str) -> Result<Rational, SolveError> {
+        if !self.monkeys.contains_key(variable) {
+            return Err(SolveError::UnknownMonkey(variable.to_owned()));
+        }
 
-                if r1.is_none() || r2.is_none() {
-                    return None;
-                }
+        let [m1, m2] = self
+            .monkeys
+            .get(monkey_name)
+            .ok_or_else(|| SolveError::UnknownMonkey(monkey_name.to_owned()))?
+            .job
+            .dependencies()
+            .ok_or_else(|| SolveError::NotAnEquation(monkey_name.to_owned()))?;
+
+        // Both sides are linear, so their difference is `a * x + b = 0`
+        let overflow = || SolveError::Overflow(monkey_name.to_owned());
+        let equation = self
+            .compile(m1, Some(variable))?
+            .checked_sub(self.compile(m2, Some(variable))?)
+            .ok_or_else(overflow)?;
+
+        if equation.slope.is_zero() {
+            return Err(if equation.constant.is_zero() {
+                SolveError::InfiniteSolutions
+            } else {
+                SolveError::NoSolution
+            });
+        }
 
-                results.insert(monkey_name.to_owned(), r1.unwrap() - r2.unwrap());
-            }
-            Expression::Mul(m1, m2) => {
-                let r1 = self.eval_evaluable_expressions(m1, results);
-                let r2 = self.eval_evaluable_expressions(m2, results);
+        equation
+            .constant
+            .checked_neg()
+            .and_then(|constant| constant.checked_div(equation.slope))
+            .ok_or_else(overflow)
+    }
 
-                if r1.is_none() || r2.is_none() {
-                    return None;
+    /// Prints the equality `monkey_name` stands for in infix notation.
+    ///
+    /// Sub expressions that do not depend on `variable` are folded into their value.
+    pub(crate) fn equation(&self, monkey_name: &str, variable: &str) -> Result<String, SolveError> {
+        let [m1, m2] = self
+            .monkeys
+            .get(monkey_name)
+            .ok_or_else(|| SolveError::UnknownMonkey(monkey_name.to_owned()))?
+            .job
+            .dependencies()
+            .ok_or_else(|| SolveError::NotAnEquation(monkey_name.to_owned()))?;
+
+        let mut texts: HashMap<&str, (String, u8)> = HashMap::new();
+        let mut symbolic = HashSet::new();
+
+        for side in [m1, m2] {
+            let values = self.compile_all(side, None)?;
+
+            for name in self.post_order(side, Some(variable))? {
+                let job = &self.monkeys[name].job;
+
+                if name == variable {
+                    symbolic.insert(name);
+                    texts.insert(name, (name.to_owned(), Expression::ATOM));
+                    continue;
                 }
 
-                results.insert(monkey_name.to_owned(), r1.unwrap() * r2.unwrap());
-            }
-            Expression::Div(m1, m2) => {
-                let r1 = self.eval_evaluable_expressions(m1, results);
-                let r2 = self.eval_evaluable_expressions(m2, results);
+                let dependencies = job.dependencies();
 
-                if r1.is_none() || r2.is_none() {
-                    return None;
+                if dependencies.is_none_or(|deps| deps.iter().all(|dep| !symbolic.contains(dep))) {
+                    let value = values[name].constant;
+                    texts.insert(name, (value.to_string(), value.precedence()));
+                    continue;
                 }
 
-                results.insert(monkey_name.to_owned(), r1.unwrap() / r2.unwrap());
-            }
-            Expression::Value(v) => {
-                results.insert(monkey_name.to_owned(), *v);
+                let [left, right] = dependencies.unwrap();
+                let precedence = job.precedence();
+                let (left, left_precedence) = &texts[left];
+                let (right, right_precedence) = &texts[right];
+
+                let left = if *left_precedence < precedence {
+                    format!("({})", left)
+                } else {
+                    left.clone()
+                };
+                let right = if *right_precedence < precedence
+                    || (*right_precedence == precedence && !job.is_associative())
+                {
+                    format!("({})", right)
+                } else {
+                    right.clone()
+                };
+
+                symbolic.insert(name);
+                texts.insert(
+                    name,
+                    (format!("{} {} {}", left, job.operator(), right), precedence),
+                );
             }
         }
 
-        Some(results[monkey_name])
+        Ok(format!("{} = {}", texts[m1].0, texts[m2].0))
     }
 
-    fn solve_equation(
-        &self,
-        monkey_name: &str,
-        results: &HashMap<String, i64>,
-        desired_result: i64,
-    ) -> i64 {
-        match &self.monkeys[monkey_name].job {
-            Expression::Add(m1, m2) => {
-                if results.contains_key(m1) && !results.contains_key(m2) {
-                    return self.solve_equation(m2, results, desired_result - results[m1]);
+    /// Graphviz description of which monkeys wait for which
+    pub(crate) fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph jungle {".to_owned()];
+
+        self.monkeys
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .for_each(|(name, monkey)| {
+                lines.push(format!(
+                    "    {} [label=\"{}: {}\"];",
+                    name, name, monkey.job
+                ));
+
+                if let Some(dependencies) = monkey.job.dependencies() {
+                    dependencies
+                        .iter()
+                        .for_each(|dep| lines.push(format!("    {} -> {};", name, dep)));
                 }
+            });
 
-                if !results.contains_key(m1) && results.contains_key(m2) {
-                    return self.solve_equation(m1, results, desired_result - results[m2]);
-                }
+        lines.push("}".to_owned());
+        lines.join("\n")
+    }
+}
 
-                unreachable!()
-            }
-            Expression::Sub(m1, m2) => {
-                if results.contains_key(m1) && !results.contains_key(m2) {
-                    return self.solve_equation(m2, results, results[m1] - desired_result);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                if !results.contains_key(m1) && results.contains_key(m2) {
-                    return self.solve_equation(m1, results, desired_result + results[m2]);
-                }
+    #[test]
+    fn solve_example() {
+        let jungle = Jungle::parse("src/day21/test");
 
-                unreachable!()
-            }
-            Expression::Mul(m1, m2) => {
-                if results.contains_key(m1) && !results.contains_key(m2) {
-                    return self.solve_equation(m2, results, desired_result / results[m1]);
-                }
+        assert_eq!(jungle.eval("root"), Ok(Rational::from(152)));
+        assert_eq!(jungle.solve("root", "humn"), Ok(Rational::from(301)));
+    }
 
-                if !results.contains_key(m1) && results.contains_key(m2) {
-                    return self.solve_equation(m1, results, desired_result / results[m2]);
-                }
+    #[test]
+    fn solve_exact_fraction() {
+        let jungle =
+            Jungle::from_str("root: aaaa + bbbb\naaaa: humn / two\ntwo: 2\nbbbb: 3\nhumn: 1")
+                .unwrap();
 
-                unreachable!()
-            }
-            Expression::Div(m1, m2) => {
-                if results.contains_key(m1) && !results.contains_key(m2) {
-                    return self.solve_equation(m2, results, results[m1] / desired_result);
-                }
+        assert_eq!(jungle.solve("root", "humn"), Ok(Rational::from(6)));
+        assert_eq!(jungle.solve("root", "bbbb"), Ok(Rational::new(1, 2)));
+    }
 
-                if !results.contains_key(m1) && results.contains_key(m2) {
-                    return self.solve_equation(m1, results, desired_result * results[m2]);
-                }
+    #[test]
+    fn solve_errors() {
+        let jungle =
+            Jungle::from_str("root: aaaa - bbbb\naaaa: humn * humn\nbbbb: 0\nhumn: 1").unwrap();
+
+        assert_eq!(
+            jungle.solve("root", "humn"),
+            Err(SolveError::NonLinear("aaaa".to_owned()))
+        );
+        assert_eq!(
+            jungle.solve("root", "cccc"),
+            Err(SolveError::UnknownMonkey("cccc".to_owned()))
+        );
+        assert_eq!(
+            jungle.solve("bbbb", "humn"),
+            Err(SolveError::NotAnEquation("bbbb".to_owned()))
+        );
+
+        let jungle = Jungle::from_str(
+            "root: aaaa + bbbb\naaaa: humn - humn\nbbbb: humn / zero\nzero: 0\nhumn: 1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            jungle.solve("root", "aaaa"),
+            Err(SolveError::DivisionByZero("bbbb".to_owned()))
+        );
+        assert_eq!(
+            jungle.solve("aaaa", "humn"),
+            Err(SolveError::InfiniteSolutions)
+        );
+        assert_eq!(
+            jungle.solve("root", "zero"),
+            Err(SolveError::NonLinear("bbbb".to_owned()))
+        );
+    }
 
-                unreachable!()
-            }
-            _ => desired_result,
-        }
+    #[test]
+    fn cycle_detection() {
+        let jungle = Jungle::from_str(
+            "root: aaaa + bbbb\naaaa: bbbb * cccc\nbbbb: 1\ncccc: dddd - bbbb\ndddd: aaaa / bbbb",
+        )
+        .unwrap();
+
+        assert_eq!(
+            jungle.eval("root"),
+            Err(SolveError::Cycle("aaaa".to_owned()))
+        );
+        assert_eq!(jungle.eval("bbbb"), Ok(Rational::from(1)));
     }
 
-    pub(crate) fn solve(&self, monkey_name: &str) -> i64 {
-        let mut evaluable_expressions = HashMap::new();
-        self.eval_evaluable_expressions(monkey_name, &mut evaluable_expressions);
+    #[test]
+    fn deep_chain() {
+        let chain = (0..100_000)
+            .map(|i| format!("m{}: m{} + one", i, i + 1))
+            .chain(["m100000: 0".to_owned(), "one: 1".to_owned()])
+            .join("\n");
+        let jungle = Jungle::from_str(&chain).unwrap();
 
-        if let Some([m1, m2]) = self.monkeys[monkey_name].job.dependencies() {
-            if evaluable_expressions.contains_key(m1) && !evaluable_expressions.contains_key(m2) {
-                return self.solve_equation(m2, &evaluable_expressions, evaluable_expressions[m1]);
-            }
+        assert_eq!(jungle.eval("m0"), Ok(Rational::from(100_000)));
+    }
 
-            if !evaluable_expressions.contains_key(m1) && evaluable_expressions.contains_key(m2) {
-                return self.solve_equation(m1, &evaluable_expressions, evaluable_expressions[m2]);
-            }
-        }
+    #[test]
+    fn overflow() {
+        // Each monkey is a third of the next one, until the denominator no longer fits
+        let chain = (0..90)
+            .map(|i| format!("m{}: m{} / three", i, i + 1))
+            .chain(["m90: humn * one".to_owned()])
+            .chain(["root: m0 - one".to_owned(), "humn: 1".to_owned()])
+            .chain(["one: 1".to_owned(), "three: 3".to_owned()])
+            .join("\n");
+        let jungle = Jungle::from_str(&chain).unwrap();
+
+        assert_eq!(jungle.eval("m10"), Ok(Rational::new(1, 3i128.pow(80))));
+        assert_eq!(
+            jungle.eval("root"),
+            Err(SolveError::Overflow("m9".to_owned()))
+        );
+        assert_eq!(
+            jungle.solve("root", "humn"),
+            Err(SolveError::Overflow("m9".to_owned()))
+        );
+    }
+
+    #[test]
+    fn equation_text() {
+        let jungle = Jungle::parse("src/day21/test");
+
+        assert_eq!(
+            jungle.equation("root", "humn").unwrap(),
+            "(4 + 2 * (humn - 3)) / 4 = 150"
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let jungle = Jungle::from_str("root: aaaa - bbbb\naaaa: 3\nbbbb: 4").unwrap();
 
-        unreachable!()
+        assert_eq!(
+            jungle.to_dot(),
+            "digraph jungle {\n    aaaa [label=\"aaaa: 3\"];\n    bbbb [label=\"bbbb: 4\"];\n    root [label=\"root: aaaa - bbbb\"];\n    root -> aaaa;\n    root -> bbbb;\n}"
+        );
     }
 }
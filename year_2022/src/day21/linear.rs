@@ -0,0 +1,49 @@
+use crate::day21::rational::Rational;
+
+/// Value of a monkey written as `slope * x + constant`, where `x` is the unknown
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Linear {
+    pub(crate) slope: Rational,
+    pub(crate) constant: Rational,
+}
+
+impl Linear {
+    pub(crate) fn constant(value: Rational) -> Self {
+        Self {
+            slope: Rational::ZERO,
+            constant: value,
+        }
+    }
+
+    pub(crate) fn variable() -> Self {
+        Self {
+            slope: Rational::ONE,
+            constant: Rational::ZERO,
+        }
+    }
+
+    pub(crate) fn as_constant(&self) -> Option<Rational> {
+        self.slope.is_zero().then_some(self.constant)
+    }
+
+    pub(crate) fn checked_scale(self, factor: Rational) -> Option<Self> {
+        Some(Self {
+            slope: self.slope.checked_mul(factor)?,
+            constant: self.constant.checked_mul(factor)?,
+        })
+    }
+
+    pub(crate) fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            slope: self.slope.checked_add(rhs.slope)?,
+            constant: self.constant.checked_add(rhs.constant)?,
+        })
+    }
+
+    pub(crate) fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            slope: self.slope.checked_sub(rhs.slope)?,
+            constant: self.constant.checked_sub(rhs.constant)?,
+        })
+    }
+}
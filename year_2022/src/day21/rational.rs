@@ -0,0 +1,164 @@
+use crate::number_theory::gcd;
+use std::fmt::{Display, Formatter};
+
+/// Exact fraction kept in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub(crate) struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub(crate) const ZERO: Self = Self {
+        numerator: 0,
+        denominator: 1,
+    };
+
+    pub(crate) const ONE: Self = Self {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Panics if `denominator` is 0, callers are expected to check it beforehand
+    pub(crate) fn new(numerator: i128, denominator: i128) -> Self {
+        assert_ne!(denominator, 0, "Rational with zero denominator");
+
+        Self::reduced(numerator, denominator).expect("Rational out of range")
+    }
+
+    /// `numerator / denominator` in lowest terms, if it fits.
+    ///
+    /// `i128::MIN` is left out, as flipping its sign to get a positive denominator
+    /// would overflow.
+    fn reduced(numerator: i128, denominator: i128) -> Option<Self> {
+        if numerator == i128::MIN || denominator == i128::MIN {
+            return None;
+        }
+
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+
+        Some(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    /// Binding strength of the printed value, as an operand of infix text
+    pub(crate) fn precedence(&self) -> u8 {
+        if self.numerator < 0 {
+            1
+        } else if self.denominator != 1 {
+            2
+        } else {
+            3
+        }
+    }
+
+    pub(crate) fn checked_add(self, rhs: Self) -> Option<Self> {
+        // Only the part of the denominators they do not share needs multiplying
+        let divisor = gcd(self.denominator, rhs.denominator);
+        let (left, right) = (self.denominator / divisor, rhs.denominator / divisor);
+
+        Self::reduced(
+            self.numerator
+                .checked_mul(right)?
+                .checked_add(rhs.numerator.checked_mul(left)?)?,
+            self.denominator.checked_mul(right)?,
+        )
+    }
+
+    pub(crate) fn checked_neg(self) -> Option<Self> {
+        Self::reduced(self.numerator.checked_neg()?, self.denominator)
+    }
+
+    pub(crate) fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub(crate) fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Both fractions are in lowest terms, so only crossed factors can cancel out
+        let first = gcd(self.numerator, rhs.denominator).max(1);
+        let second = gcd(rhs.numerator, self.denominator).max(1);
+
+        Self::reduced(
+            (self.numerator / first).checked_mul(rhs.numerator / second)?,
+            (self.denominator / second).checked_mul(rhs.denominator / first)?,
+        )
+    }
+
+    /// `None` when dividing by zero, as well as on overflow
+    pub(crate) fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+
+        self.checked_mul(Self::reduced(rhs.denominator, rhs.numerator)?)
+    }
+
+    /// The value as an integer, if it has no fractional part
+    pub(crate) fn to_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 1)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowest_terms() {
+        assert_eq!(Rational::new(4, -6), Rational::new(-2, 3));
+        assert_eq!(Rational::new(4, -6).to_string(), "-2/3");
+        assert_eq!(
+            Rational::from(7)
+                .checked_div(Rational::from(2))
+                .and_then(|half| half.checked_mul(Rational::from(2))),
+            Some(Rational::from(7))
+        );
+        assert_eq!(
+            Rational::from(7)
+                .checked_div(Rational::from(2))
+                .and_then(Rational::to_integer),
+            None
+        );
+        assert_eq!(
+            Rational::new(1, 6).checked_sub(Rational::new(1, 4)),
+            Some(Rational::new(-1, 12))
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let big = Rational::from(i64::MAX)
+            .checked_mul(Rational::from(i64::MAX))
+            .unwrap();
+
+        assert_eq!(big.checked_mul(Rational::from(4)), None);
+        assert_eq!(big.checked_neg().and_then(Rational::checked_neg), Some(big));
+        assert_eq!(Rational::ONE.checked_div(Rational::ZERO), None);
+
+        // Crossed factors cancel before multiplying
+        let inverse = Rational::ONE.checked_div(big).unwrap();
+        assert_eq!(big.checked_mul(inverse), Some(Rational::ONE));
+    }
+}
@@ -1,107 +1,39 @@
-use crate::day23::direction::Direction;
-use crate::day23::elf::{Elf, Pixel};
-use crate::day23::position::Position;
-use itertools::Itertools;
-use std::borrow::BorrowMut;
-use std::collections::{HashMap, HashSet, LinkedList};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use strum::IntoEnumIterator;
+pub use crate::day23::board::Bounds;
+pub use crate::day23::direction::Direction;
+pub use crate::day23::rules::{Conflict, Proposal, Rotation, Rules};
+pub use crate::day23::simulation::{Outcome, RoundStats, Simulation};
 
+mod board;
 mod direction;
-mod elf;
+mod plane;
 mod position;
+mod rules;
+mod simulation;
 
 pub struct Day23;
 
 impl Day23 {
-    fn read_elves(path: &str) -> HashSet<Elf> {
-        BufReader::new(File::open(path).unwrap())
-            .lines()
-            .enumerate()
-            .flat_map(|(i, line)| {
-                line.unwrap()
-                    .chars()
-                    .enumerate()
-                    .filter_map(|(j, c)| match Pixel::from(c) {
-                        Pixel::Land => None,
-                        Pixel::Elf => Some(Elf::new(Position(i as i32, j as i32))),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect()
+    pub fn part_one() -> usize {
+        let mut simulation = Simulation::parse("src/day23/input", Rules::default());
+        (0..10).for_each(|_| {
+            simulation.step();
+        });
+        simulation.empty_ground()
     }
 
-    fn simulate(rounds: usize, others: &mut HashSet<Elf>) -> usize {
-        let mut directions = LinkedList::from([
-            Direction::North,
-            Direction::South,
-            Direction::West,
-            Direction::East,
-        ]);
-
-        for i in 0..rounds {
-            let mut destinations = HashMap::new();
-            let mut proposals = HashMap::new();
-
-            for elf in others.iter().filter(|elf| (*elf).can_move(others)) {
-                for direction_now in directions.iter() {
-                    if let Some(destination) = elf.destination(direction_now, others) {
-                        destinations
-                            .entry(destination)
-                            .or_insert_with(Vec::new)
-                            .push(elf.clone());
-                        proposals
-                            .entry(elf.clone())
-                            .or_insert_with(Vec::new)
-                            .push(destination);
-                        break;
-                    }
-                }
-            }
-
-            if proposals.is_empty() {
-                return i + 1;
-            }
-
-            proposals.into_iter().for_each(|(elf, elf_proposals)| {
-                for proposal in &elf_proposals {
-                    if destinations[&proposal].len() == 1 {
-                        others.remove(&elf);
-                        others.insert(Elf::new(*proposal));
-                        break;
-                    }
-                }
-            });
-
-            let direction = directions.pop_front().unwrap();
-            directions.push_back(direction);
+    pub fn part_two() -> usize {
+        match Simulation::parse("src/day23/input", Rules::default()).run(usize::MAX) {
+            Outcome::Settled(round) => round,
+            outcome => panic!("Elves never settled: {:?}", outcome),
         }
-
-        return rounds;
     }
 
-    pub fn part_one() -> usize {
-        let mut elves = Self::read_elves("src/day23/input");
-        Self::simulate(10, &mut elves);
-        let mut bot_left_corner = Position(i32::MAX, i32::MAX);
-        let mut top_right_corner = Position(i32::MIN, i32::MIN);
-
-        elves.iter().for_each(|elf| {
-            bot_left_corner.0 = bot_left_corner.0.min(elf.position.0);
-            bot_left_corner.1 = bot_left_corner.1.min(elf.position.1);
-
-            top_right_corner.0 = top_right_corner.0.max(elf.position.0);
-            top_right_corner.1 = top_right_corner.1.max(elf.position.1);
-        });
-
-        ((top_right_corner.1 - bot_left_corner.1 + 1)
-            * (top_right_corner.0 - bot_left_corner.0 + 1)) as usize
-            - elves.len()
-    }
+    /// Statistics of every round played under `rules` until the elves settle or
+    /// repeat themselves, at most `max_rounds` rounds
+    pub fn analyse(path: &str, rules: Rules, max_rounds: usize) -> (Vec<RoundStats>, Outcome) {
+        let mut simulation = Simulation::parse(path, rules);
+        let outcome = simulation.run(max_rounds);
 
-    pub fn part_two() -> usize {
-        let mut elves = Self::read_elves("src/day23/input");
-        Self::simulate(usize::MAX, &mut elves)
+        (simulation.history().to_vec(), outcome)
     }
 }
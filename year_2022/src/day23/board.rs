@@ -0,0 +1,325 @@
+use crate::day23::direction::Direction;
+use crate::day23::plane::Plane;
+use crate::day23::position::Position;
+use crate::day23::rules::{Conflict, Rules};
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+/// Rows and words of empty ground added around the elves whenever one reaches the border
+const GROWTH_ROWS: usize = 16;
+const GROWTH_WORDS: usize = 1;
+
+pub(crate) enum Pixel {
+    Elf,
+    Land,
+}
+
+impl TryFrom<char> for Pixel {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '#' => Ok(Self::Elf),
+            '.' => Ok(Self::Land),
+            _ => Err(format!("Unknown pixel {}", c)),
+        }
+    }
+}
+
+/// Elves packed one bit per cell, every rule being applied to all the elves of
+/// 64 columns at once through shifted copies of the board
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Board {
+    elves: Plane,
+
+    // Cell of the plane holding the top left corner of the input
+    origin: (usize, usize),
+}
+
+/// Bounding box of the elves in input coordinates, as its top left and bottom right
+/// corners given as `(row, column)`
+pub type Bounds = ((i64, i64), (i64, i64));
+
+/// Elves of a board cropped to their bounding box, together with where that box is
+pub(crate) type Snapshot = ((i64, i64), Plane);
+
+impl FromStr for Board {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let mut elves = Plane::new(lines.len().max(1), width.div_ceil(64).max(1));
+
+        for (i, line) in lines.iter().enumerate() {
+            for (j, c) in line.chars().enumerate() {
+                if let Pixel::Elf = Pixel::try_from(c)? {
+                    elves.set(i, j);
+                }
+            }
+        }
+
+        Ok(Self {
+            elves,
+            origin: (0, 0),
+        })
+    }
+}
+
+impl Board {
+    pub(crate) fn parse(path: &str) -> Self {
+        Self::from_str(&read_to_string(path).unwrap()).unwrap()
+    }
+
+    pub(crate) fn elves(&self) -> usize {
+        self.elves.count_ones()
+    }
+
+    /// Plane of the cells having an elf towards `direction`
+    fn neighbours(&self, direction: &Direction) -> Plane {
+        let offset = Position::from(direction);
+        self.elves.shifted(offset.0, offset.1)
+    }
+
+    fn empty(&self) -> Plane {
+        Plane::new(self.elves.height, self.elves.words)
+    }
+
+    /// Plays one round of `rules`, after `offset` rotations of the proposals, and
+    /// returns how many elves proposed to move and how many actually moved
+    pub(crate) fn round(&mut self, rules: &Rules, offset: usize) -> (usize, usize) {
+        if self.elves.touches_border() {
+            self.elves = self.elves.padded(GROWTH_ROWS, GROWTH_WORDS);
+            self.origin.0 += GROWTH_ROWS;
+            self.origin.1 += GROWTH_WORDS * u64::BITS as usize;
+        }
+
+        let neighbours = Direction::iter()
+            .map(|direction| (direction, self.neighbours(&direction)))
+            .collect::<Vec<_>>();
+        let neighbour = |direction: &Direction| {
+            &neighbours
+                .iter()
+                .find(|(other, _)| other == direction)
+                .unwrap()
+                .1
+        };
+        let any_neighbour = |directions: &mut dyn Iterator<Item = &Direction>| {
+            directions.fold(self.empty(), |acc, direction| acc.or(neighbour(direction)))
+        };
+
+        let active = self
+            .elves
+            .and(&any_neighbour(&mut rules.neighbourhood.iter()));
+        let mut undecided = active.clone();
+
+        // The target itself must be free, whatever the proposal asks to be clear
+        let proposals = rules
+            .proposals(offset)
+            .map(|proposal| {
+                let blocked =
+                    any_neighbour(&mut proposal.clear.iter().chain([&proposal.direction]));
+                let elves = undecided.and_not(&blocked);
+                undecided = undecided.and_not(&elves);
+
+                (Position::from(&proposal.direction), elves)
+            })
+            .collect::<Vec<_>>();
+
+        let proposing = active.count_ones() - undecided.count_ones();
+
+        // Elves moving with each proposal, as the cells they leave
+        let movers = match rules.conflict {
+            Conflict::NobodyMoves => {
+                // Cells targeted once and cells targeted at least twice
+                let mut targeted = self.empty();
+                let mut conflicts = self.empty();
+
+                for (offset, elves) in &proposals {
+                    let targets = elves.shifted(-offset.0, -offset.1);
+                    conflicts = conflicts.or(&targeted.and(&targets));
+                    targeted = targeted.or(&targets);
+                }
+
+                proposals
+                    .iter()
+                    .map(|(offset, elves)| {
+                        (
+                            *offset,
+                            elves.and_not(&conflicts.shifted(offset.0, offset.1)),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+            Conflict::FirstProposalWins => {
+                // Elves of a single proposal never share a target, only earlier ones can
+                let mut claimed = self.empty();
+
+                proposals
+                    .iter()
+                    .map(|(offset, elves)| {
+                        let moving = elves.and_not(&claimed.shifted(offset.0, offset.1));
+                        claimed = claimed.or(&elves.shifted(-offset.0, -offset.1));
+
+                        (*offset, moving)
+                    })
+                    .collect()
+            }
+        };
+
+        let mut moved = 0;
+
+        // Targets are free cells, so removing and adding elves one direction at a time is safe
+        for (offset, moving) in &movers {
+            moved += moving.count_ones();
+            self.elves = self
+                .elves
+                .and_not(moving)
+                .or(&moving.shifted(-offset.0, -offset.1));
+        }
+
+        (proposing, moved)
+    }
+
+    /// Smallest rectangle holding every elf, if any
+    pub(crate) fn bounds(&self) -> Option<Bounds> {
+        let origin = (self.origin.0 as i64, self.origin.1 as i64);
+
+        self.elves.bounds().map(|(min, max)| {
+            (
+                (min.0 as i64 - origin.0, min.1 as i64 - origin.1),
+                (max.0 as i64 - origin.0, max.1 as i64 - origin.1),
+            )
+        })
+    }
+
+    /// Elves as a value equal for boards with the same elves, however far they grew
+    pub(crate) fn snapshot(&self) -> Option<Snapshot> {
+        let (min, max) = self.elves.bounds()?;
+
+        Some((
+            (
+                min.0 as i64 - self.origin.0 as i64,
+                min.1 as i64 - self.origin.1 as i64,
+            ),
+            self.elves.cropped(min, max),
+        ))
+    }
+
+    /// Empty cells in the smallest rectangle holding every elf
+    pub(crate) fn empty_ground(&self) -> usize {
+        self.elves
+            .bounds()
+            .map_or(0, |(min, max)| (max.0 - min.0 + 1) * (max.1 - min.1 + 1))
+            - self.elves()
+    }
+}
+
+impl Display for Board {
+    /// Smallest rectangle holding every elf
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some((min, max)) = self.elves.bounds() else {
+            return Ok(());
+        };
+
+        let rows = (min.0..=max.0)
+            .map(|row| {
+                (min.1..=max.1)
+                    .map(|col| if self.elves.get(row, col) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day23::rules::{Proposal, Rotation};
+
+    fn play(board: &mut Board, rules: &Rules, rounds: usize) {
+        (0..rounds).for_each(|round| {
+            board.round(rules, round);
+        });
+    }
+
+    #[test]
+    fn small_example() {
+        let mut board = Board::parse("src/day23/test_small");
+        play(&mut board, &Rules::default(), 3);
+
+        assert_eq!(
+            board.to_string(),
+            "..#..\n....#\n#....\n....#\n.....\n..#.."
+        );
+        assert_eq!(board.bounds(), Some(((0, 0), (5, 4))));
+    }
+
+    #[test]
+    fn example() {
+        let rules = Rules::default();
+        let mut board = Board::parse("src/day23/test");
+        play(&mut board, &rules, 10);
+
+        assert_eq!(board.empty_ground(), 110);
+
+        let mut round = 10;
+
+        while board.round(&rules, round).1 > 0 {
+            round += 1;
+        }
+
+        assert_eq!(round + 1, 20);
+        assert_eq!(board.elves(), 22);
+    }
+
+    #[test]
+    fn grows_when_elves_reach_the_border() {
+        let mut board = Board::from_str("#\n#").unwrap();
+        let snapshot = board.snapshot();
+        board.round(&Rules::default(), 0);
+
+        assert_eq!(board.to_string(), "#\n.\n.\n#");
+        assert_eq!(board.elves.height, 2 + 2 * GROWTH_ROWS);
+        assert_eq!(board.elves.words, 1 + 2 * GROWTH_WORDS);
+        assert_eq!(board.bounds(), Some(((-1, 0), (2, 0))));
+        assert_ne!(board.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn conflicts() {
+        // The two elves of the bottom row both want the cell between them
+        let rules = Rules {
+            proposals: vec![
+                Proposal {
+                    direction: Direction::East,
+                    clear: vec![Direction::NorthEast],
+                },
+                Proposal {
+                    direction: Direction::West,
+                    clear: vec![],
+                },
+            ],
+            rotation: Rotation::Fixed,
+            neighbourhood: Direction::iter().collect(),
+            conflict: Conflict::FirstProposalWins,
+        };
+        let mut board = Board::from_str("#..#\n#.#.").unwrap();
+
+        assert_eq!(board.round(&rules, 0), (4, 3));
+        assert_eq!(board.to_string(), "#..#\n##..");
+
+        let rules = Rules {
+            conflict: Conflict::NobodyMoves,
+            ..rules
+        };
+        let mut board = Board::from_str("#..#\n#.#.").unwrap();
+
+        assert_eq!(board.round(&rules, 0), (4, 2));
+        assert_eq!(board.to_string(), ".#..#\n#.#..");
+    }
+}
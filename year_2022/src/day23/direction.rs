@@ -1,7 +1,7 @@
 use strum_macros::EnumIter;
 
-#[derive(Debug, Eq, PartialEq, EnumIter)]
-pub(crate) enum Direction {
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, EnumIter)]
+pub enum Direction {
     North,
     South,
     East,
@@ -13,13 +13,41 @@ pub(crate) enum Direction {
 }
 
 impl Direction {
-    pub(crate) fn dependencies(&self) -> [Self; 3] {
+    /// The direction itself and the two directions 45 degrees away from it
+    pub fn dependencies(&self) -> [Self; 3] {
         match *self {
             Self::North => [Self::North, Self::NorthEast, Self::NorthWest],
             Self::South => [Self::South, Self::SouthEast, Self::SouthWest],
             Self::West => [Self::West, Self::NorthWest, Self::SouthWest],
             Self::East => [Self::East, Self::NorthEast, Self::SouthEast],
-            _ => unreachable!(),
+            Self::NorthEast => [Self::NorthEast, Self::North, Self::East],
+            Self::NorthWest => [Self::NorthWest, Self::North, Self::West],
+            Self::SouthEast => [Self::SouthEast, Self::South, Self::East],
+            Self::SouthWest => [Self::SouthWest, Self::South, Self::West],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day23::position::Position;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn dependencies_are_next_to_the_direction() {
+        for direction in Direction::iter() {
+            let target = Position::from(&direction);
+            let [first, second, third] = direction.dependencies();
+
+            assert_eq!(first, direction);
+            assert!(second != third && second != direction && third != direction);
+
+            for dependency in direction.dependencies() {
+                let position = Position::from(&dependency);
+
+                assert!((position.0 - target.0).abs() + (position.1 - target.1).abs() <= 1);
+            }
         }
     }
 }
@@ -0,0 +1,202 @@
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Rectangle of bits, one row after the other, each row packed in `words` words.
+///
+/// Column `c` of a row is bit `c % 64` of its word `c / 64`, so every operation
+/// handles 64 columns at once.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub(crate) struct Plane {
+    pub(crate) height: usize,
+    pub(crate) words: usize,
+    bits: Vec<u64>,
+}
+
+impl Plane {
+    pub(crate) fn new(height: usize, words: usize) -> Self {
+        Self {
+            height,
+            words,
+            bits: vec![0; height * words],
+        }
+    }
+
+    pub(crate) fn get(&self, row: usize, col: usize) -> bool {
+        self.bits[row * self.words + col / WORD_BITS] & (1 << (col % WORD_BITS)) != 0
+    }
+
+    pub(crate) fn set(&mut self, row: usize, col: usize) {
+        self.bits[row * self.words + col / WORD_BITS] |= 1 << (col % WORD_BITS);
+    }
+
+    pub(crate) fn count_ones(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub(crate) fn and(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a & b)
+    }
+
+    pub(crate) fn and_not(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a & !b)
+    }
+
+    pub(crate) fn or(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a | b)
+    }
+
+    fn zip(&self, other: &Self, operation: impl Fn(u64, u64) -> u64) -> Self {
+        Self {
+            height: self.height,
+            words: self.words,
+            bits: self
+                .bits
+                .iter()
+                .zip(&other.bits)
+                .map(|(a, b)| operation(*a, *b))
+                .collect(),
+        }
+    }
+
+    /// Plane whose bit `(r, c)` is bit `(r + dy, c + dx)` of this one, or 0 outside of it.
+    ///
+    /// `dx` is -1, 0 or 1, carrying bits across the words of a row.
+    pub(crate) fn shifted(&self, dy: i32, dx: i32) -> Self {
+        let mut shifted = Self::new(self.height, self.words);
+
+        for row in 0..self.height {
+            let source = row as i64 + dy as i64;
+
+            if source < 0 || source >= self.height as i64 {
+                continue;
+            }
+
+            let source = &self.bits[source as usize * self.words..][..self.words];
+            let target = &mut shifted.bits[row * self.words..][..self.words];
+
+            for w in 0..self.words {
+                target[w] = match dx {
+                    0 => source[w],
+                    // Column c takes column c - 1, from the top bit of the previous word at c = 0
+                    -1 => (source[w] << 1) | w.checked_sub(1).map_or(0, |p| source[p] >> 63),
+                    1 => (source[w] >> 1) | source.get(w + 1).map_or(0, |n| n << 63),
+                    _ => unreachable!("Columns can only be shifted by one"),
+                };
+            }
+        }
+
+        shifted
+    }
+
+    /// Same bits surrounded by `rows` empty rows above and below, and `words`
+    /// empty words on the left and on the right of every row
+    pub(crate) fn padded(&self, rows: usize, words: usize) -> Self {
+        let mut padded = Self::new(self.height + 2 * rows, self.words + 2 * words);
+
+        for row in 0..self.height {
+            let start = (row + rows) * padded.words + words;
+            padded.bits[start..start + self.words]
+                .copy_from_slice(&self.bits[row * self.words..][..self.words]);
+        }
+
+        padded
+    }
+
+    /// Bits of the rectangle between the `min` and `max` corners, moved to the top left
+    pub(crate) fn cropped(&self, min: (usize, usize), max: (usize, usize)) -> Self {
+        let words = (max.1 - min.1 + 1).div_ceil(WORD_BITS);
+        let mut cropped = Self::new(max.0 - min.0 + 1, words);
+        let (first, shift) = (min.1 / WORD_BITS, min.1 % WORD_BITS);
+
+        for row in min.0..=max.0 {
+            let source = &self.bits[row * self.words..][..self.words];
+            let target = &mut cropped.bits[(row - min.0) * words..][..words];
+
+            for (w, word) in target.iter_mut().enumerate() {
+                let low = source.get(first + w).map_or(0, |word| word >> shift);
+                let high = match shift {
+                    0 => 0,
+                    _ => source
+                        .get(first + w + 1)
+                        .map_or(0, |word| word << (64 - shift)),
+                };
+
+                *word = low | high;
+            }
+
+            // Columns past `max` would tell apart crops of the same rectangle
+            let extra = words * WORD_BITS - (max.1 - min.1 + 1);
+            target[words - 1] &= u64::MAX >> extra;
+        }
+
+        cropped
+    }
+
+    /// Whether any bit is set on the first or last row or column
+    pub(crate) fn touches_border(&self) -> bool {
+        let last_row = (self.height - 1) * self.words;
+
+        self.bits[..self.words].iter().any(|word| *word != 0)
+            || self.bits[last_row..].iter().any(|word| *word != 0)
+            || (0..self.height).any(|row| {
+                self.bits[row * self.words] & 1 != 0
+                    || self.bits[(row + 1) * self.words - 1] >> 63 != 0
+            })
+    }
+
+    /// Smallest and largest row and column holding a bit, if any
+    pub(crate) fn bounds(&self) -> Option<((usize, usize), (usize, usize))> {
+        let mut bounds: Option<((usize, usize), (usize, usize))> = None;
+
+        for row in 0..self.height {
+            for w in 0..self.words {
+                let word = self.bits[row * self.words + w];
+
+                if word == 0 {
+                    continue;
+                }
+
+                let first = w * WORD_BITS + word.trailing_zeros() as usize;
+                let last = w * WORD_BITS + (WORD_BITS - 1 - word.leading_zeros() as usize);
+
+                bounds = Some(match bounds {
+                    None => ((row, first), (row, last)),
+                    Some((min, max)) => ((min.0, min.1.min(first)), (row, max.1.max(last))),
+                });
+            }
+        }
+
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_across_words() {
+        let mut plane = Plane::new(3, 2);
+        plane.set(1, 63);
+        plane.set(1, 64);
+
+        let west = plane.shifted(0, -1);
+        assert!(west.get(1, 64) && west.get(1, 65) && !west.get(1, 63));
+
+        let east = plane.shifted(0, 1);
+        assert!(east.get(1, 62) && east.get(1, 63) && !east.get(1, 64));
+
+        let south = plane.shifted(-1, 1);
+        assert!(south.get(2, 62) && south.get(2, 63) && south.count_ones() == 2);
+
+        assert_eq!(plane.bounds(), Some(((1, 63), (1, 64))));
+        assert!(!plane.touches_border());
+        assert!(plane.padded(1, 1).get(2, 127));
+
+        let cropped = plane.padded(1, 1).cropped((2, 126), (2, 128));
+        assert_eq!((cropped.height, cropped.words), (1, 1));
+        assert!(!cropped.get(0, 0) && cropped.get(0, 1) && cropped.get(0, 2));
+    }
+}
@@ -1,17 +1,8 @@
 use crate::day23::direction::Direction;
-use std::ops::Add;
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub(crate) struct Position(pub(crate) i32, pub(crate) i32);
 
-impl Add for Position {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
 impl From<&Direction> for Position {
     fn from(direction: &Direction) -> Self {
         match *direction {
@@ -0,0 +1,78 @@
+use crate::day23::direction::Direction;
+use strum::IntoEnumIterator;
+
+/// An elf may move towards `direction` if that cell is free and no elf stands towards
+/// any of `clear`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Proposal {
+    pub direction: Direction,
+    pub clear: Vec<Direction>,
+}
+
+/// How the order of the proposals changes after every round
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Rotation {
+    Fixed,
+    /// The first `n` proposals move to the back
+    RotateLeft(usize),
+}
+
+/// What happens when several elves propose the same cell
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Conflict {
+    /// None of them moves
+    NobodyMoves,
+    /// The elf whose proposal comes first in the current order moves, the others stay
+    FirstProposalWins,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rules {
+    pub proposals: Vec<Proposal>,
+    pub rotation: Rotation,
+    /// An elf only proposes to move if some elf stands towards one of these directions
+    pub neighbourhood: Vec<Direction>,
+    pub conflict: Conflict,
+}
+
+impl Default for Rules {
+    /// Rules of the puzzle
+    fn default() -> Self {
+        Self {
+            proposals: [
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ]
+            .into_iter()
+            .map(|direction| Proposal {
+                direction,
+                clear: direction.dependencies().to_vec(),
+            })
+            .collect(),
+            rotation: Rotation::RotateLeft(1),
+            neighbourhood: Direction::iter().collect(),
+            conflict: Conflict::NobodyMoves,
+        }
+    }
+}
+
+impl Rules {
+    /// Proposals in the order used after `offset` rotations
+    pub(crate) fn proposals(&self, offset: usize) -> impl Iterator<Item = &Proposal> {
+        self.proposals
+            .iter()
+            .cycle()
+            .skip(offset % self.proposals.len().max(1))
+            .take(self.proposals.len())
+    }
+
+    /// Rotation offset of the round after one using `offset`
+    pub(crate) fn next_offset(&self, offset: usize) -> usize {
+        match self.rotation {
+            Rotation::Fixed => offset,
+            Rotation::RotateLeft(n) => (offset + n) % self.proposals.len().max(1),
+        }
+    }
+}
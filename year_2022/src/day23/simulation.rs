@@ -0,0 +1,183 @@
+use crate::day23::board::{Board, Bounds, Snapshot};
+use crate::day23::rules::Rules;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// What happened during one round
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoundStats {
+    pub round: usize,
+    pub proposing: usize,
+    pub moved: usize,
+    /// Bounding box after the round, see `Bounds`
+    pub bounds: Option<Bounds>,
+    pub empty_ground: usize,
+}
+
+/// How a simulation ended
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Outcome {
+    /// No elf moved during this round
+    Settled(usize),
+    /// After `start` rounds, the elves and the proposal order repeat every `period` rounds
+    Periodic { start: usize, period: usize },
+    /// Neither happened within the allowed rounds
+    Exhausted,
+}
+
+/// Elves spreading out round after round under some `Rules`
+pub struct Simulation {
+    board: Board,
+    rules: Rules,
+    offset: usize,
+    round: usize,
+    history: Vec<RoundStats>,
+}
+
+impl Simulation {
+    pub fn new(grove: &str, rules: Rules) -> Result<Self, String> {
+        Ok(Self {
+            board: Board::from_str(grove)?,
+            rules,
+            offset: 0,
+            round: 0,
+            history: vec![],
+        })
+    }
+
+    pub fn parse(path: &str, rules: Rules) -> Self {
+        Self {
+            board: Board::parse(path),
+            rules,
+            offset: 0,
+            round: 0,
+            history: vec![],
+        }
+    }
+
+    /// Rounds played so far
+    pub fn rounds(&self) -> usize {
+        self.round
+    }
+
+    /// Statistics of every round played so far
+    pub fn history(&self) -> &[RoundStats] {
+        &self.history
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.board.bounds()
+    }
+
+    pub fn empty_ground(&self) -> usize {
+        self.board.empty_ground()
+    }
+
+    /// Plays the next round
+    pub fn step(&mut self) -> RoundStats {
+        let (proposing, moved) = self.board.round(&self.rules, self.offset);
+        self.offset = self.rules.next_offset(self.offset);
+        self.round += 1;
+
+        let stats = RoundStats {
+            round: self.round,
+            proposing,
+            moved,
+            bounds: self.board.bounds(),
+            empty_ground: self.board.empty_ground(),
+        };
+        self.history.push(stats.clone());
+
+        stats
+    }
+
+    /// Plays until a round in which nobody moves or until the configuration comes back
+    /// to an earlier one, for at most `max_rounds` more rounds.
+    ///
+    /// A configuration is the position of every elf together with the order the
+    /// proposals will be tried in, since the same elves may spread differently after
+    /// a rotation.
+    pub fn run(&mut self, max_rounds: usize) -> Outcome {
+        let mut seen: HashMap<(Option<Snapshot>, usize), usize> = HashMap::new();
+
+        for _ in 0..max_rounds {
+            let configuration = (self.board.snapshot(), self.offset);
+
+            if let Some(start) = seen.insert(configuration, self.round) {
+                return Outcome::Periodic {
+                    start,
+                    period: self.round - start,
+                };
+            }
+
+            let stats = self.step();
+
+            if stats.moved == 0 {
+                return Outcome::Settled(stats.round);
+            }
+        }
+
+        Outcome::Exhausted
+    }
+}
+
+impl Display for Simulation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day23::direction::Direction;
+    use crate::day23::rules::{Conflict, Proposal, Rotation};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn example() {
+        let mut simulation = Simulation::parse("src/day23/test", Rules::default());
+        let stats = (0..10).map(|_| simulation.step()).collect::<Vec<_>>();
+
+        assert_eq!(stats[9].round, 10);
+        assert_eq!(stats[9].empty_ground, 110);
+        assert_eq!(stats[9].bounds, Some(((0, 1), (10, 12))));
+        assert_eq!(simulation.run(100), Outcome::Settled(20));
+        assert_eq!(simulation.rounds(), 20);
+        assert_eq!(simulation.history()[..10], stats);
+        assert_eq!(simulation.history()[19].moved, 0);
+    }
+
+    #[test]
+    fn periodic() {
+        // A pair walks north, then back south once the order rotates
+        let towards = |direction| Proposal {
+            direction,
+            clear: vec![],
+        };
+        let rules = Rules {
+            proposals: vec![towards(Direction::North), towards(Direction::South)],
+            rotation: Rotation::RotateLeft(1),
+            neighbourhood: Direction::iter().collect(),
+            conflict: Conflict::NobodyMoves,
+        };
+        let mut simulation = Simulation::new("##", rules).unwrap();
+
+        assert_eq!(
+            simulation.run(100),
+            Outcome::Periodic {
+                start: 0,
+                period: 2
+            }
+        );
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut simulation = Simulation::parse("src/day23/test", Rules::default());
+
+        assert_eq!(simulation.run(5), Outcome::Exhausted);
+        assert_eq!(simulation.rounds(), 5);
+    }
+}
@@ -1,16 +1,20 @@
 use crate::day24::blizzard::Blizzard;
 use crate::day24::direction::Direction;
+pub use crate::day24::expedition::{Expedition, Move, Waypoint};
 use crate::day24::grid::Grid;
 use crate::day24::pixel::Pixel;
 use crate::day24::position::Position;
+use crate::day24::valley::Valley;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 mod blizzard;
 mod direction;
+mod expedition;
 mod grid;
 mod pixel;
 mod position;
+mod valley;
 
 pub struct Day24;
 
@@ -50,47 +54,41 @@ impl Day24 {
         Grid::new(num_columns, grid_rows)
     }
 
-    pub fn part_one() -> usize {
-        let grid = Self::parse("src/day24/input");
+    /// Plans a trip through the valley described by the file at `path`
+    pub fn plan(path: &str, waypoints: &[Waypoint]) -> Option<Expedition> {
+        let grid = Self::parse(path);
+        let valley = Valley::new(&grid);
+        let positions = waypoints
+            .iter()
+            .map(|waypoint| match *waypoint {
+                Waypoint::Start => Position(1, 1),
+                Waypoint::Goal => Position(grid.pixels.len() as i32 - 2, grid.width as i32 - 2),
 
-        let (distance, _) = grid
-            .a_star(
-                Position(1, 1),
-                Position(grid.pixels.len() as i32 - 2, grid.width as i32 - 2),
-                &|a, b| a.manhattan_distance(b),
-            )
-            .unwrap();
+                // The parsed grid has an extra row of rock on top
+                Waypoint::At(row, column) => Position(row as i32 + 1, column as i32),
+            })
+            .collect::<Vec<_>>();
 
-        distance
+        valley.plan(&positions, &|a, b| a.manhattan_distance(b))
     }
 
-    pub fn part_two() -> usize {
-        let grid = Self::parse("src/day24/input");
-
-        let (distance1, weather_map1) = grid
-            .a_star(
-                Position(1, 1),
-                Position(grid.pixels.len() as i32 - 2, grid.width as i32 - 2),
-                &|a, b| a.manhattan_distance(b),
-            )
-            .unwrap();
-
-        let (distance2, weather_map2) = weather_map1
-            .a_star(
-                Position(grid.pixels.len() as i32 - 2, grid.width as i32 - 2),
-                Position(1, 1),
-                &|a, b| a.manhattan_distance(b),
-            )
-            .unwrap();
-
-        let (distance3, _) = weather_map2
-            .a_star(
-                Position(1, 1),
-                Position(grid.pixels.len() as i32 - 2, grid.width as i32 - 2),
-                &|a, b| a.manhattan_distance(b),
-            )
-            .unwrap();
+    pub fn part_one() -> usize {
+        Self::plan("src/day24/input", &[Waypoint::Start, Waypoint::Goal])
+            .unwrap()
+            .total_time
+    }
 
-        distance1 + distance2 + distance3
+    pub fn part_two() -> usize {
+        Self::plan(
+            "src/day24/input",
+            &[
+                Waypoint::Start,
+                Waypoint::Goal,
+                Waypoint::Start,
+                Waypoint::Goal,
+            ],
+        )
+        .unwrap()
+        .total_time
     }
 }
@@ -16,16 +16,17 @@ impl Blizzard {
         }
     }
 
-    pub(crate) fn change_position(&mut self, grid: &Grid) {
-        self.position += Position::from(&self.direction);
+    /// Blizzards move in straight lines and wrap around, so their position
+    /// after `time` minutes can be computed without simulating the steps
+    pub(crate) fn position_at(&self, grid: &Grid, time: usize) -> Position {
+        let offset = Position::from(&self.direction);
+        let mut position = self.position;
 
-        match self.direction {
-            Direction::Up | Direction::Down => {
-                self.position.0 = (self.position.0 - 2).rem_euclid(grid.pixels.len() as i32 - 4) + 2
-            }
-            Direction::Left | Direction::Right => {
-                self.position.1 = (self.position.1 - 1).rem_euclid(grid.width as i32 - 2) + 1
-            }
-        }
+        position.0 =
+            (position.0 - 2 + offset.0 * time as i32).rem_euclid(grid.inner_height() as i32) + 2;
+        position.1 =
+            (position.1 - 1 + offset.1 * time as i32).rem_euclid(grid.inner_width() as i32) + 1;
+
+        position
     }
 }
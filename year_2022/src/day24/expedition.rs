@@ -0,0 +1,91 @@
+use crate::day24::direction::Direction;
+use std::fmt::{Display, Formatter};
+
+/// Points of interest the expedition has to visit, in puzzle coordinates
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Waypoint {
+    Start,
+    Goal,
+    At(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Move {
+    Wait,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl From<&Direction> for Move {
+    fn from(direction: &Direction) -> Self {
+        match *direction {
+            Direction::Up => Self::Up,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+            Direction::Right => Self::Right,
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Wait => write!(f, "wait"),
+            Move::Up => write!(f, "move up"),
+            Move::Down => write!(f, "move down"),
+            Move::Left => write!(f, "move left"),
+            Move::Right => write!(f, "move right"),
+        }
+    }
+}
+
+/// Outcome of visiting a list of waypoints in order.
+///
+/// `legs` keeps the minutes spent between consecutive waypoints, while `moves`
+/// has one entry per minute of the whole trip, waits included.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Expedition {
+    pub total_time: usize,
+    pub legs: Vec<usize>,
+    pub moves: Vec<Move>,
+}
+
+impl Display for Expedition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (minute, step) in self.moves.iter().enumerate() {
+            writeln!(f, "Minute {}, {}.", minute + 1, step)?;
+        }
+
+        write!(
+            f,
+            "Total time: {} ({})",
+            self.total_time,
+            self.legs
+                .iter()
+                .map(|leg| leg.to_string())
+                .collect::<Vec<_>>()
+                .join(" + ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let expedition = Expedition {
+            total_time: 3,
+            legs: vec![2, 1],
+            moves: vec![Move::Down, Move::Wait, Move::Up],
+        };
+
+        assert_eq!(
+            expedition.to_string(),
+            "Minute 1, move down.\nMinute 2, wait.\nMinute 3, move up.\nTotal time: 3 (2 + 1)"
+        );
+    }
+}
@@ -5,7 +5,6 @@ pub(crate) enum Pixel {
     Land,
     Rock,
     Blizzard(Blizzard),
-    Blizzards(Vec<Blizzard>),
 }
 
 impl From<&Pixel> for char {
@@ -14,9 +13,6 @@ impl From<&Pixel> for char {
             Pixel::Land => '.',
             Pixel::Rock => '#',
             Pixel::Blizzard(blizzard) => (&blizzard.direction).into(),
-
-            // Can't have more than 4 blizzards on a single position
-            Pixel::Blizzards(blizzards) => ('0' as u8 + blizzards.len() as u8) as char,
         }
     }
 }
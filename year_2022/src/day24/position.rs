@@ -1,16 +1,9 @@
 use crate::day24::direction::Direction;
-use std::ops::{Add, AddAssign};
+use std::ops::Add;
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub(crate) struct Position(pub(crate) i32, pub(crate) i32);
 
-impl AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
-    }
-}
-
 impl Add for Position {
     type Output = Self;
 
@@ -0,0 +1,227 @@
+use crate::day24::direction::Direction;
+use crate::day24::expedition::{Expedition, Move};
+use crate::day24::grid::Grid;
+use crate::day24::pixel::Pixel;
+use crate::day24::position::Position;
+use crate::number_theory::lcm;
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use strum::IntoEnumIterator;
+
+pub(crate) type Heuristic = dyn Fn(&Position, &Position) -> i32;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Index of the next waypoint to visit, position and minute of the expedition
+type State = (usize, Position, usize);
+
+/// Blizzards come back to their initial positions every `lcm(inner height, inner width)`
+/// minutes, so the valley keeps one occupancy bitset per minute of that period
+/// instead of stepping the whole grid during the search.
+#[derive(Debug)]
+pub(crate) struct Valley {
+    pub(crate) height: usize,
+    pub(crate) width: usize,
+    pub(crate) period: usize,
+    occupancy: Vec<Vec<u64>>,
+}
+
+impl Valley {
+    pub(crate) fn new(grid: &Grid) -> Self {
+        let height = grid.pixels.len();
+        let width = grid.width;
+        let period = lcm(grid.inner_height(), grid.inner_width());
+        let mut walls = vec![0u64; (height * width).div_ceil(WORD_BITS)];
+
+        grid.pixels.iter().enumerate().for_each(|(i, pixels)| {
+            pixels.iter().enumerate().for_each(|(j, pixel)| {
+                if *pixel == Pixel::Rock {
+                    Self::set(&mut walls, i * width + j);
+                }
+            })
+        });
+
+        let blizzards = grid
+            .pixels
+            .iter()
+            .flatten()
+            .filter_map(|pixel| match pixel {
+                Pixel::Blizzard(blizzard) => Some(blizzard),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let occupancy = (0..period)
+            .map(|time| {
+                let mut bits = walls.clone();
+
+                blizzards.iter().for_each(|blizzard| {
+                    let position = blizzard.position_at(grid, time);
+                    Self::set(&mut bits, position.0 as usize * width + position.1 as usize);
+                });
+
+                bits
+            })
+            .collect();
+
+        Self {
+            height,
+            width,
+            period,
+            occupancy,
+        }
+    }
+
+    fn set(bits: &mut [u64], index: usize) {
+        bits[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    /// Tells whether the expedition can stand at `position` at minute `time`
+    pub(crate) fn is_free(&self, position: &Position, time: usize) -> bool {
+        if position.0 < 0
+            || position.1 < 0
+            || position.0 as usize >= self.height
+            || position.1 as usize >= self.width
+        {
+            return false;
+        }
+
+        let index = position.0 as usize * self.width + position.1 as usize;
+        self.occupancy[time % self.period][index / WORD_BITS] & (1 << (index % WORD_BITS)) == 0
+    }
+
+    /// Visits `waypoints` in order, as early as possible.
+    ///
+    /// All the legs are searched together over `(next waypoint, position, time % period)`
+    /// states: a waypoint inside the valley may be a spot the expedition cannot wait on,
+    /// so arriving there as early as possible is not always part of the fastest trip.
+    pub(crate) fn plan(&self, waypoints: &[Position], heuristic: &Heuristic) -> Option<Expedition> {
+        let last = waypoints.len().checked_sub(1)?;
+
+        // Index of the next waypoint once `position` is reached, consecutive equal
+        // waypoints being visited at once
+        let advance = |mut next: usize, position: &Position| {
+            while next <= last && waypoints[next] == *position {
+                next += 1;
+            }
+
+            next
+        };
+
+        // Lower bound of the minutes left, walking every remaining leg without blizzards
+        let remaining = |next: usize, position: &Position| {
+            (next..=last)
+                .map(|i| match i {
+                    _ if i == next => heuristic(position, &waypoints[i]),
+                    _ => heuristic(&waypoints[i - 1], &waypoints[i]),
+                })
+                .sum::<i32>() as usize
+        };
+
+        let start = (advance(0, &waypoints[0]), waypoints[0], 0);
+        let mut visited = HashSet::new();
+        let mut parents = HashMap::new();
+        let mut open: PriorityQueue<State, Reverse<usize>> = PriorityQueue::new();
+
+        open.push(start, Reverse(remaining(start.0, &start.1)));
+
+        while let Some(((next, position, time), _)) = open.pop() {
+            if next > last {
+                return Some(Self::expedition(&parents, (next, position, time)));
+            }
+
+            if !visited.insert((next, position, time % self.period)) {
+                continue;
+            }
+
+            // Waiting is just another move that keeps the same position
+            Direction::iter()
+                .map(|direction| {
+                    (
+                        position + Position::from(&direction),
+                        Move::from(&direction),
+                    )
+                })
+                .chain(std::iter::once((position, Move::Wait)))
+                .filter(|(position, _)| self.is_free(position, time + 1))
+                .map(|(position, step)| ((advance(next, &position), position, time + 1), step))
+                .filter(|((next, position, time), _)| {
+                    !visited.contains(&(*next, *position, time % self.period))
+                })
+                .for_each(|(state, step)| {
+                    // Every parent of a state reaches it after the same number of minutes
+                    parents
+                        .entry(state)
+                        .or_insert(((next, position, time), step));
+                    open.push(state, Reverse(state.2 + remaining(state.0, &state.1)));
+                });
+        }
+
+        None
+    }
+
+    /// Trip ending with `state`, the minutes of a leg ending whenever the state moves
+    /// on to a later waypoint
+    fn expedition(parents: &HashMap<State, (State, Move)>, mut state: State) -> Expedition {
+        let mut moves = Vec::with_capacity(state.2);
+        let mut arrivals = vec![];
+
+        while let Some((parent, step)) = parents.get(&state) {
+            (parent.0..state.0).for_each(|_| arrivals.push(state.2));
+            moves.push(*step);
+            state = *parent;
+        }
+
+        // Waypoints already reached at the start, the first one at least
+        (0..state.0).for_each(|_| arrivals.push(0));
+        moves.reverse();
+        arrivals.reverse();
+
+        Expedition {
+            total_time: moves.len(),
+            legs: arrivals.windows(2).map(|pair| pair[1] - pair[0]).collect(),
+            moves,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day24::{Day24, Waypoint};
+
+    #[test]
+    fn example() {
+        let expedition = Day24::plan(
+            "src/day24/test",
+            &[
+                Waypoint::Start,
+                Waypoint::Goal,
+                Waypoint::Start,
+                Waypoint::Goal,
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(expedition.total_time, 54);
+        assert_eq!(expedition.legs, vec![18, 23, 13]);
+        assert_eq!(expedition.moves.len(), 54);
+    }
+
+    #[test]
+    fn waypoint_reached_later_than_possible() {
+        // Reaching the waypoint at minute 2 leaves it in the way of blizzards and the
+        // trip ends at minute 17, while getting there at minute 5 lets it end at 12
+        let waypoints = [Waypoint::Start, Waypoint::At(1, 2), Waypoint::Goal];
+        let expedition = Day24::plan("src/day24/test_detour", &waypoints).unwrap();
+
+        assert_eq!(expedition.total_time, 12);
+        assert_eq!(expedition.legs, vec![5, 7]);
+        assert_eq!(
+            Day24::plan("src/day24/test_detour", &waypoints[..2])
+                .unwrap()
+                .total_time,
+            2
+        );
+    }
+}
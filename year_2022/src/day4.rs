@@ -1,38 +1,14 @@
+use crate::interval_set::IntervalSet;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
-use std::str::FromStr;
 
 pub struct Day4;
 
-struct Interval {
-    left: usize,
-    right: usize,
-}
-
-impl Interval {
-    fn contains(&self, other: &Self) -> bool {
-        self.left <= other.left && self.right >= other.right
-    }
-
-    fn overlap(&self, other: &Self) -> bool {
-        !(other.right < self.left
-            || self.left > other.right
-            || self.right < other.left
-            || self.right < other.left)
-    }
-}
+fn parse_interval(s: &str) -> IntervalSet<usize> {
+    let (left, right) = s.split_once('-').unwrap();
 
-impl FromStr for Interval {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens = s.split("-").collect::<Vec<&str>>();
-        Ok(Self {
-            left: (&tokens[0]).parse::<usize>().unwrap(),
-            right: (&tokens[1]).parse::<usize>().unwrap(),
-        })
-    }
+    IntervalSet::from(left.parse::<usize>().unwrap()..=right.parse::<usize>().unwrap())
 }
 
 impl Day4 {
@@ -44,12 +20,9 @@ impl Day4 {
         for line in buffer_reader.lines() {
             let content = line.unwrap();
             let tokens = content.split(",").collect::<Vec<&str>>();
-            let (i1, i2) = (
-                Interval::from_str(&tokens[0]).unwrap(),
-                Interval::from_str(&tokens[1]).unwrap(),
-            );
+            let (i1, i2) = (parse_interval(tokens[0]), parse_interval(tokens[1]));
 
-            result += (i1.contains(&i2) || i2.contains(&i1)) as usize;
+            result += (i2.is_subset(&i1) || i1.is_subset(&i2)) as usize;
         }
 
         result
@@ -63,12 +36,9 @@ impl Day4 {
         for line in buffer_reader.lines() {
             let content = line.unwrap();
             let tokens = content.split(",").collect::<Vec<&str>>();
-            let (i1, i2) = (
-                Interval::from_str(&tokens[0]).unwrap(),
-                Interval::from_str(&tokens[1]).unwrap(),
-            );
+            let (i1, i2) = (parse_interval(tokens[0]), parse_interval(tokens[1]));
 
-            result += i1.overlap(&i2) as usize;
+            result += !i1.is_disjoint(&i2) as usize;
         }
 
         result
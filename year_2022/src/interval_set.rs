@@ -0,0 +1,265 @@
+use num_traits::PrimInt;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Set of integers stored as disjoint inclusive intervals.
+///
+/// Intervals are kept sorted by their start and merged as soon as they overlap or
+/// touch, so `{1..=2, 3..=5}` is always stored as `{1..=5}`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct IntervalSet<T: PrimInt> {
+    // Start -> end of every interval
+    intervals: BTreeMap<T, T>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Disjoint intervals of the set, sorted by their start
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.iter().map(|(start, end)| *start..=*end)
+    }
+
+    /// Number of integers in the set, or `None` if it does not fit in a `u128`, which
+    /// only happens when a 128-bit set holds every value of its type
+    pub fn total_length(&self) -> Option<u128> {
+        self.intervals
+            .iter()
+            .try_fold(0u128, |total, (start, end)| {
+                // `end - start` may overflow `T`, but never `u128`
+                let width = match (start.to_i128(), end.to_i128()) {
+                    (Some(start), Some(end)) => end.abs_diff(start),
+                    _ => end.to_u128()? - start.to_u128()?,
+                };
+
+                total.checked_add(width.checked_add(1)?)
+            })
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.intervals
+            .range(..=*value)
+            .next_back()
+            .is_some_and(|(_, end)| end >= value)
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+
+        if start > end {
+            return;
+        }
+
+        // An interval starting before `start` may overlap or touch the new one
+        if let Some((&previous_start, &previous_end)) = self.intervals.range(..start).next_back() {
+            if previous_end >= start.saturating_sub(T::one()) {
+                start = previous_start;
+                end = end.max(previous_end);
+            }
+        }
+
+        let absorbed = self
+            .intervals
+            .range(start..=end.saturating_add(T::one()))
+            .map(|(start, end)| (*start, *end))
+            .collect::<Vec<_>>();
+
+        for (absorbed_start, absorbed_end) in absorbed {
+            self.intervals.remove(&absorbed_start);
+            end = end.max(absorbed_end);
+        }
+
+        self.intervals.insert(start, end);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+
+        if start > end {
+            return;
+        }
+
+        // An interval starting before `start` keeps its part on the left of `start`
+        if let Some((&previous_start, &previous_end)) = self.intervals.range(..start).next_back() {
+            if previous_end >= start {
+                self.intervals.insert(previous_start, start - T::one());
+
+                if previous_end > end {
+                    self.intervals.insert(end + T::one(), previous_end);
+                }
+            }
+        }
+
+        let removed = self
+            .intervals
+            .range(start..=end)
+            .map(|(start, end)| (*start, *end))
+            .collect::<Vec<_>>();
+
+        for (removed_start, removed_end) in removed {
+            self.intervals.remove(&removed_start);
+
+            if removed_end > end {
+                self.intervals.insert(end + T::one(), removed_end);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        other.iter().for_each(|range| union.insert(range));
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection = Self::new();
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+
+            if start <= end {
+                intersection.insert(start..=end);
+            }
+
+            // The interval ending first cannot meet anything else on the other side
+            if a.end() < b.end() {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+
+        intersection
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        other.iter().for_each(|range| difference.remove(range));
+        difference
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    /// Intervals missing between the first and the last interval of the set
+    pub fn gaps(&self) -> Vec<RangeInclusive<T>> {
+        self.iter()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|pair| *pair[0].end() + T::one()..=*pair[1].start() - T::one())
+            .collect()
+    }
+}
+
+impl<T: PrimInt> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: PrimInt> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|range| set.insert(range));
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(set: &IntervalSet<i32>) -> Vec<RangeInclusive<i32>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn insert_merges_touching_intervals() {
+        let set = IntervalSet::from_iter([5..=7, 1..=2, 3..=3, 10..=12, 6..=10]);
+
+        assert_eq!(ranges(&set), vec![1..=3, 5..=12]);
+        assert_eq!(set.total_length(), Some(11));
+        assert_eq!(set.gaps(), vec![4..=4]);
+        assert!(set.contains(&3) && set.contains(&12) && !set.contains(&4));
+    }
+
+    #[test]
+    fn remove_splits_intervals() {
+        let mut set = IntervalSet::from_iter([1..=10, 15..=20, 25..=30]);
+        set.remove(5..=16);
+        set.remove(30..=30);
+        set.remove(0..=0);
+
+        assert_eq!(ranges(&set), vec![1..=4, 17..=20, 25..=29]);
+
+        set.remove(i32::MIN..=i32::MAX);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = IntervalSet::from_iter([1..=5, 10..=15]);
+        let b = IntervalSet::from_iter([4..=11, 14..=14, 20..=21]);
+
+        assert_eq!(ranges(&a.union(&b)), vec![1..=15, 20..=21]);
+        assert_eq!(ranges(&a.intersection(&b)), vec![4..=5, 10..=11, 14..=14]);
+        assert_eq!(ranges(&a.difference(&b)), vec![1..=3, 12..=13, 15..=15]);
+        assert!(IntervalSet::from(2..=4).is_subset(&a));
+        assert!(!b.is_subset(&a));
+        assert!(IntervalSet::from(6..=9).is_disjoint(&a));
+    }
+
+    #[test]
+    fn extreme_bounds() {
+        let set = IntervalSet::from_iter([i32::MIN..=-1, 0..=i32::MAX]);
+
+        assert_eq!(ranges(&set), vec![i32::MIN..=i32::MAX]);
+        assert_eq!(set.total_length(), Some(1 << 32));
+    }
+
+    #[test]
+    fn total_length_of_wide_types() {
+        assert_eq!(
+            IntervalSet::from(i64::MIN..=i64::MAX).total_length(),
+            Some(1 << 64)
+        );
+        assert_eq!(
+            IntervalSet::from(u64::MIN..=u64::MAX).total_length(),
+            Some(1 << 64)
+        );
+        assert_eq!(
+            IntervalSet::from_iter([0..=0, u128::MAX - 1..=u128::MAX]).total_length(),
+            Some(3)
+        );
+        assert_eq!(
+            IntervalSet::from_iter([i128::MIN..=-1, 1..=i128::MAX]).total_length(),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            IntervalSet::from(i128::MIN..=i128::MAX).total_length(),
+            None
+        );
+        assert_eq!(
+            IntervalSet::from(u128::MIN..=u128::MAX).total_length(),
+            None
+        );
+    }
+}
@@ -22,3 +22,6 @@ pub mod day5;
 pub mod day6;
 pub mod day8;
 pub mod day9;
+pub mod interval_set;
+pub mod number_theory;
+pub mod ocr;
@@ -0,0 +1,39 @@
+use num_traits::PrimInt;
+
+/// Greatest common divisor, never negative, with `gcd(0, 0) = 0`
+pub fn gcd<T: PrimInt>(mut a: T, mut b: T) -> T {
+    while b != T::zero() {
+        (a, b) = (b, a % b);
+    }
+
+    if a < T::zero() {
+        T::zero() - a
+    } else {
+        a
+    }
+}
+
+/// Least common multiple, 0 if either number is 0
+pub fn lcm<T: PrimInt>(a: T, b: T) -> T {
+    if a == T::zero() || b == T::zero() {
+        return T::zero();
+    }
+
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_divisors_and_multiples() {
+        assert_eq!(gcd(12usize, 18), 6);
+        assert_eq!(gcd(-4i128, 6), 2);
+        assert_eq!(gcd(4i128, -6), 2);
+        assert_eq!(gcd(0u32, 0), 0);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm(100usize, 35), 700);
+        assert_eq!(lcm(0usize, 35), 0);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter};
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+
+/// Empty column between two consecutive letters
+const GLYPH_SPACING: usize = 1;
+
+/// Letters of the 4x6 font used by the puzzles, rows joined top to bottom
+const FONT: [(char, &str); 17] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OcrError {
+    Height(usize),
+    /// A row does not have the width of the first one
+    RaggedRow(usize),
+    /// The width does not split into whole letters and the spaces between them
+    Width(usize),
+    /// A column meant to separate two letters has lit pixels
+    LitSpacing(usize),
+    UnknownGlyph {
+        index: usize,
+        glyph: String,
+    },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::Height(height) => write!(
+                f,
+                "Expected {} rows of pixels, found {}",
+                GLYPH_HEIGHT, height
+            ),
+            OcrError::RaggedRow(row) => {
+                write!(f, "Row {} does not have the width of the first one", row)
+            }
+            OcrError::Width(width) => write!(
+                f,
+                "A width of {} pixels is not a whole number of letters {} pixels wide",
+                width, GLYPH_WIDTH
+            ),
+            OcrError::LitSpacing(column) => {
+                write!(f, "Column {} separates letters but has lit pixels", column)
+            }
+            OcrError::UnknownGlyph { index, glyph } => {
+                write!(f, "Cannot recognize letter {}:\n{}", index, glyph)
+            }
+        }
+    }
+}
+
+/// Reads the capital letters drawn with `#` in `screen`, one row of pixels per line.
+///
+/// Any other character is an unlit pixel.
+pub fn recognize(screen: &str) -> Result<String, OcrError> {
+    let rows = screen
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::Height(rows.len()));
+    }
+
+    let width = rows[0].len();
+
+    if let Some(row) = rows.iter().position(|row| row.len() != width) {
+        return Err(OcrError::RaggedRow(row));
+    }
+
+    // The space after the last letter is optional
+    let letters = (width + GLYPH_SPACING) / (GLYPH_WIDTH + GLYPH_SPACING);
+
+    if letters == 0 || (width + GLYPH_SPACING) % (GLYPH_WIDTH + GLYPH_SPACING) > GLYPH_SPACING {
+        return Err(OcrError::Width(width));
+    }
+
+    if let Some(column) = (GLYPH_WIDTH..width)
+        .filter(|column| column % (GLYPH_WIDTH + GLYPH_SPACING) >= GLYPH_WIDTH)
+        .find(|column| rows.iter().any(|row| row[*column]))
+    {
+        return Err(OcrError::LitSpacing(column));
+    }
+
+    (0..letters)
+        .map(|index| {
+            let start = index * (GLYPH_WIDTH + GLYPH_SPACING);
+            let glyph = rows
+                .iter()
+                .flat_map(|row| &row[start..start + GLYPH_WIDTH])
+                .map(|lit| if *lit { '#' } else { '.' })
+                .collect::<String>();
+
+            FONT.iter()
+                .find(|(_, pixels)| *pixels == glyph)
+                .map(|(letter, _)| *letter)
+                .ok_or_else(|| OcrError::UnknownGlyph {
+                    index,
+                    glyph: glyph
+                        .as_bytes()
+                        .chunks(GLYPH_WIDTH)
+                        .map(|row| String::from_utf8_lossy(row).into_owned())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognize_letters() {
+        let screen = "\
+####..##...##..#..#.####.###..####..##..
+#....#..#.#..#.#..#....#.#..#.#....#..#.
+###..#....#....#..#...#..#..#.###..#....
+#....#.##.#....#..#..#...###..#....#....
+#....#..#.#..#.#..#.#....#.#..#....#..#.
+#.....###..##...##..####.#..#.####..##..
+";
+
+        assert_eq!(recognize(screen), Ok("FGCUZREC".to_owned()));
+    }
+
+    #[test]
+    fn partial_glyph() {
+        let letter = |extra: &str| {
+            ["#...", "#...", "#...", "#...", "#...", "####"]
+                .map(|row| format!("{}{}", row, extra))
+                .join("\n")
+        };
+
+        assert_eq!(recognize(&letter("")), Ok("L".to_owned()));
+        assert_eq!(recognize(&letter(".")), Ok("L".to_owned()));
+        assert_eq!(recognize(&letter("#")), Err(OcrError::LitSpacing(4)));
+        assert_eq!(recognize(&letter(".#")), Err(OcrError::Width(6)));
+        assert_eq!(recognize(&letter("....")), Err(OcrError::Width(8)));
+        assert_eq!(recognize("#\n#\n#\n#\n#\n#"), Err(OcrError::Width(1)));
+        assert_eq!(recognize("\n\n\n\n\n\n"), Err(OcrError::Width(0)));
+        assert_eq!(
+            recognize("#...\n#...\n#\n#...\n#...\n####"),
+            Err(OcrError::RaggedRow(2))
+        );
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        let screen = "#..#.####\n#..#.#..#\n####.#..#\n#..#.#..#\n#..#.#..#\n#..#.####\n";
+
+        assert_eq!(
+            recognize(screen),
+            Err(OcrError::UnknownGlyph {
+                index: 1,
+                glyph: "####\n#..#\n#..#\n#..#\n#..#\n####".to_owned()
+            })
+        );
+    }
+}